@@ -1,135 +1,472 @@
+use crate::domain::diff::{DiffEntry, TreeDiff};
 use crate::domain::entities::{
-    FileStats, FileType, Recommendation, RecommendationCategory, SortOrder,
+    AuditEntry, AuditRecord, Column, DeletionResult, FileStats, FileType, Filesystem,
+    Recommendation, RecommendationCategory, Removal, ScanError, ScanProgress, SizeMode,
+    SnapshotMeta, SortOrder, SortSpec,
 };
-use crate::domain::ports::{Analyzer, Cleaner, ScanEvent};
-use std::path::PathBuf;
+use crate::domain::history::HistoryRecord;
+use crate::domain::ports::{
+    Analyzer, AuditLog, Cleaner, DryRunLog, MountLister, ScanEvent, ScanHandle, Scanner,
+};
+use crate::domain::safety::{Blocked, SafetyRules};
+use crate::domain::tree::{DirTree, NodeId};
+use crate::domain::treemap::{self, Direction, Rect};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::Receiver;
 
+/// Files up to this apparent size count as small: one block or less.
+const SMALL_FILE_SIZE: u64 = 4096;
+const DEFAULT_SMALL_FILES_THRESHOLD: usize = 10_000;
+const MAX_SMALL_FILE_DIRS: usize = 5;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AppMode {
     Scanning,
     Browsing,
     DeleteConfirmation,
+    Marked,
+    BatchDeleteConfirmation,
     Dashboard,
     DashboardCleanupConfirmation,
+    Treemap,
+    Errors,
+    Diff,
+    Trends,
+    Filesystems,
+    Audit,
     About,
 }
 
 pub struct App {
     pub current_path: PathBuf,
+    pub current_dir: NodeId,
     pub mode: AppMode,
     pub scan_receiver: Option<Receiver<ScanEvent>>,
-    pub files: Vec<FileStats>,
-    pub total_size: u64,
-    pub scanned_count: usize,
+    pub scan_handle: Option<ScanHandle>,
+    pub tree: DirTree,
+    pub size_mode: SizeMode,
     pub is_scanning: bool,
-    pub sort_order: SortOrder,
+    /// Set when a scan of the whole tree was stopped before it finished.
+    pub is_partial: bool,
+    /// Metadata of the snapshot being browsed, if loaded from one.
+    pub snapshot: Option<SnapshotMeta>,
+    /// The ncdu export being browsed, if imported from one.
+    pub import: Option<PathBuf>,
+    pub progress: ScanProgress,
+    pub errors: Vec<ScanError>,
+    pub error_selection: usize,
+    pub sort: SortSpec,
+    /// Optional browser columns, in display order.
+    pub columns: Vec<Column>,
+    /// Hides the optional columns without forgetting them.
+    pub show_columns: bool,
     pub selection: usize,
+    /// Selected tile in the treemap, indexing `treemap_children`.
+    pub treemap_selection: usize,
     pub item_to_delete: Option<PathBuf>,
+    /// Entries marked for batch deletion, possibly in different directories.
+    pub marked: BTreeSet<PathBuf>,
+    pub marked_selection: usize,
+    /// Per-item results of the last batch deletion.
+    pub deletion_results: Vec<DeletionResult>,
+    /// Items removed by the last delete, batch or cleanup.
+    pub last_removed: usize,
+    /// Outcome of the last undo, shown in the footer.
+    pub notice: Option<String>,
+    /// Set for dry runs: what the deletions so far would have removed.
+    pub dry_run: Option<DryRunLog>,
+    /// What deletions must never touch, and how much one may remove
+    /// before asking twice.
+    pub safety: SafetyRules,
+    /// Bytes and items of an operation over the safety limits, waiting for
+    /// a second confirmation.
+    pub over_limit: Option<(u64, usize)>,
+    /// Audit log records, newest first, for the audit view.
+    pub audit_records: Vec<AuditRecord>,
+    pub audit_selection: usize,
     pub recommendations: Vec<Recommendation>,
     pub recommendation_selection: usize,
+    pub diff: Option<TreeDiff>,
+    /// Directory shown in the diff view, relative to the scan roots.
+    pub diff_path: PathBuf,
+    pub diff_selection: usize,
+    /// Recorded scans of the root, oldest first, for the trends view.
+    pub history: Vec<HistoryRecord>,
+    /// Small files a directory must hold to be flagged on the dashboard.
+    pub small_files_threshold: usize,
+    pub filesystems: Vec<Filesystem>,
+    pub filesystem_selection: usize,
 
     // Dependencies
     pub cleaner: Box<dyn Cleaner>,
     pub analyzer: Box<dyn Analyzer>,
+    pub scanner: Option<Box<dyn Scanner>>,
+    pub mounts: Option<Box<dyn MountLister>>,
+    pub audit: Option<Box<dyn AuditLog>>,
 }
 
 impl App {
     pub fn new(path: String, cleaner: Box<dyn Cleaner>, analyzer: Box<dyn Analyzer>) -> App {
+        let tree = DirTree::new(&path);
         App {
             current_path: PathBuf::from(path),
+            current_dir: tree.root(),
             mode: AppMode::Scanning,
             scan_receiver: None,
-            files: Vec::new(),
-            total_size: 0,
-            scanned_count: 0,
+            scan_handle: None,
+            tree,
+            size_mode: SizeMode::default(),
             is_scanning: true,
-            sort_order: SortOrder::Desc,
+            is_partial: false,
+            snapshot: None,
+            import: None,
+            progress: ScanProgress::default(),
+            errors: Vec::new(),
+            error_selection: 0,
+            sort: SortSpec::default(),
+            columns: vec![Column::Bar, Column::Percent, Column::Count],
+            show_columns: true,
             selection: 0,
+            treemap_selection: 0,
             item_to_delete: None,
+            marked: BTreeSet::new(),
+            marked_selection: 0,
+            deletion_results: Vec::new(),
+            last_removed: 0,
+            notice: None,
+            dry_run: None,
+            safety: SafetyRules::default(),
+            over_limit: None,
+            audit_records: Vec::new(),
+            audit_selection: 0,
             recommendations: Vec::new(),
             recommendation_selection: 0,
+            diff: None,
+            diff_path: PathBuf::new(),
+            diff_selection: 0,
+            history: Vec::new(),
+            small_files_threshold: DEFAULT_SMALL_FILES_THRESHOLD,
+            filesystems: Vec::new(),
+            filesystem_selection: 0,
             cleaner,
             analyzer,
+            scanner: None,
+            mounts: None,
+            audit: None,
+        }
+    }
+
+    /// Starts walking `root` with the configured scanner. Entries are merged
+    /// into the existing tree as they arrive.
+    pub fn start_scan(&mut self, root: &Path) {
+        let Some(scanner) = &self.scanner else {
+            return;
+        };
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.scan_handle = Some(scanner.scan(root, tx));
+        self.scan_receiver = Some(rx);
+        self.is_scanning = true;
+        if root == self.tree.root_path() {
+            self.is_partial = false;
+        }
+    }
+
+    /// Stops the running scan and leaves the partial results browsable.
+    pub fn cancel_scan(&mut self) {
+        if let Some(handle) = self.scan_handle.take() {
+            handle.cancel();
+            self.is_partial = true;
+        }
+        self.is_scanning = false;
+        if self.mode == AppMode::Scanning {
+            self.mode = AppMode::Browsing;
+        }
+    }
+
+    pub fn toggle_pause(&mut self) {
+        if let Some(handle) = &self.scan_handle {
+            if handle.is_paused() {
+                handle.resume();
+            } else {
+                handle.pause();
+            }
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.scan_handle.as_ref().is_some_and(|h| h.is_paused())
+    }
+
+    /// Re-walks the selected directory (or the current one when a file is
+    /// selected), replacing its contents in the tree with fresh results.
+    pub fn rescan_selected(&mut self) {
+        if self.is_scanning || self.scanner.is_none() || self.refuse_if_read_only("rescanning") {
+            return;
+        }
+        let id = match self.selected_node() {
+            Some(id) if self.tree.node(id).is_dir => id,
+            _ => self.current_dir,
+        };
+        self.rescan_node(id);
+    }
+
+    /// Drops the contents of directory `id` along with the errors found in
+    /// it, and walks it again.
+    fn rescan_node(&mut self, id: NodeId) {
+        if self.scanner.is_none() {
+            return;
+        }
+        for child in self.tree.children(id).to_vec() {
+            self.tree.remove(child);
+        }
+        self.leave_removed_dir();
+        self.selection = 0;
+        let path = self.tree.path(id);
+
+        self.errors
+            .retain(|error| !error.path.as_ref().is_some_and(|p| p.starts_with(&path)));
+        self.error_selection = self
+            .error_selection
+            .min(self.errors.len().saturating_sub(1));
+        // Ancestors stay incomplete only if errors outside the subtree say so.
+        self.tree.clear_incomplete(id);
+        for error in &self.errors {
+            if let Some(path) = &error.path {
+                self.tree.mark_incomplete(path);
+            }
+        }
+
+        // Hard links counted outside the subtree must not be counted again.
+        let counted = self
+            .tree
+            .iter()
+            .map(|id| self.tree.node(id))
+            .filter(|node| !node.duplicate_inode)
+            .filter_map(|node| node.inode)
+            .collect();
+        let Some(scanner) = &self.scanner else {
+            return;
+        };
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.scan_handle = Some(scanner.rescan(&path, counted, tx));
+        self.scan_receiver = Some(rx);
+        self.is_scanning = true;
+        if id == self.tree.root() {
+            self.is_partial = false;
         }
     }
 
     pub fn on_tick(&mut self) {
-        if let Some(rx) = &self.scan_receiver {
-            let mut needs_sort = false;
+        if let Some(rx) = self.scan_receiver.take() {
             for _ in 0..100 {
                 match rx.try_recv() {
-                    Ok(event) => match event {
-                        ScanEvent::NewEntry(stats) => {
-                            self.total_size += stats.size;
-                            self.files.push(stats);
-                            self.scanned_count += 1;
-                            needs_sort = true;
-                        }
-                        ScanEvent::Progress {
-                            total_size: _,
-                            files_scanned: _,
-                        } => {}
-                        ScanEvent::Complete => {
-                            self.is_scanning = false;
-                            self.mode = AppMode::Browsing;
-                            needs_sort = true;
-                        }
-                        ScanEvent::Error(err) => {
-                            eprintln!("Scan error: {}", err);
-                        }
-                    },
+                    Ok(event) => self.handle_scan_event(event),
                     Err(_) => break,
                 }
             }
 
-            if needs_sort && !self.is_scanning {
-                self.sort_files();
+            self.scan_receiver = Some(rx);
+        }
+    }
+
+    /// Blocks until the running scan completes, passing every event to
+    /// `observe` before applying it. Used when running without the TUI.
+    pub fn wait_for_scan(&mut self, mut observe: impl FnMut(&ScanEvent)) {
+        if let Some(rx) = self.scan_receiver.take() {
+            while self.is_scanning {
+                match rx.recv() {
+                    Ok(event) => {
+                        observe(&event);
+                        self.handle_scan_event(event);
+                    }
+                    Err(_) => {
+                        // The scanner went away without reporting completion.
+                        self.is_scanning = false;
+                        self.scan_handle = None;
+                    }
+                }
+            }
+
+            self.scan_receiver = Some(rx);
+        }
+    }
+
+    fn handle_scan_event(&mut self, event: ScanEvent) {
+        match event {
+            ScanEvent::NewEntry(stats) => {
+                self.add_entry(stats);
+            }
+            ScanEvent::Progress(progress) => {
+                self.progress = progress;
+            }
+            ScanEvent::Complete => {
+                self.is_scanning = false;
+                self.scan_handle = None;
+                if self.mode == AppMode::Scanning {
+                    self.mode = AppMode::Browsing;
+                }
+            }
+            ScanEvent::Error(err) => self.record_error(err),
+        }
+    }
+
+    /// Keeps a scan error for the errors view and flags the affected
+    /// directories as having an incomplete size.
+    pub fn record_error(&mut self, error: ScanError) {
+        if let Some(path) = &error.path {
+            self.tree.mark_incomplete(path);
+        }
+        self.errors.push(error);
+    }
+
+    pub fn errors_next(&mut self) {
+        if !self.errors.is_empty() {
+            self.error_selection = (self.error_selection + 1) % self.errors.len();
+        }
+    }
+
+    pub fn errors_prev(&mut self) {
+        if !self.errors.is_empty() {
+            if self.error_selection == 0 {
+                self.error_selection = self.errors.len() - 1;
+            } else {
+                self.error_selection -= 1;
             }
         }
     }
 
+    /// Adds a scanned entry to the tree. Directory sizes and entry counts are
+    /// rolled up by the tree, so they grow live while the walk progresses.
+    pub fn add_entry(&mut self, stats: FileStats) {
+        self.tree.insert(stats);
+    }
+
+    /// Total size of everything scanned, in the current size mode.
+    pub fn total_size(&self) -> u64 {
+        self.tree.node(self.tree.root()).size(self.size_mode)
+    }
+
+    /// Removes the entry at `path` and its subtree from the tree.
+    fn remove_entry(&mut self, path: &Path) -> Option<FileStats> {
+        self.marked.retain(|marked| !marked.starts_with(path));
+        let id = self.tree.find(path)?;
+        let removed = self.tree.remove(id);
+        self.leave_removed_dir();
+        removed
+    }
+
+    /// Moves the browser up to the nearest ancestor still in the tree once
+    /// the directory being viewed has been removed, as its id may be reused.
+    fn leave_removed_dir(&mut self) {
+        if self.tree.find(&self.current_path) == Some(self.current_dir) {
+            return;
+        }
+        let id = self
+            .current_path
+            .ancestors()
+            .find_map(|path| self.tree.find(path))
+            .unwrap_or(self.tree.root());
+        self.current_dir = id;
+        self.current_path = self.tree.path(id);
+        self.selection = 0;
+        self.treemap_selection = 0;
+    }
+
+    pub fn toggle_size_mode(&mut self) {
+        self.set_size_mode(self.size_mode.toggle());
+    }
+
+    /// Switches between counting entries and measuring bytes.
+    pub fn toggle_count_mode(&mut self) {
+        self.set_size_mode(self.size_mode.toggle_count());
+    }
+
+    fn set_size_mode(&mut self, mode: SizeMode) {
+        self.size_mode = mode;
+        if self.mode == AppMode::Dashboard {
+            self.scan_dashboard();
+        }
+    }
+
+    /// Flips the sort direction of the current key.
     pub fn toggle_sort(&mut self) {
-        self.sort_order = match self.sort_order {
-            SortOrder::Desc => SortOrder::Asc,
-            SortOrder::Asc => SortOrder::Desc,
+        self.sort.order = self.sort.order.reverse();
+    }
+
+    /// Moves on to the next sort key, in that key's natural direction.
+    pub fn cycle_sort_key(&mut self) {
+        self.sort = SortSpec {
+            dirs_first: self.sort.dirs_first,
+            ..SortSpec::new(self.sort.key.next())
         };
-        self.sort_files();
+        self.selection = 0;
     }
 
-    fn sort_files(&mut self) {
-        self.files.sort_by(|a, b| match self.sort_order {
-            SortOrder::Desc => b.size.cmp(&a.size),
-            SortOrder::Asc => a.size.cmp(&b.size),
-        });
+    pub fn toggle_dirs_first(&mut self) {
+        self.sort.dirs_first = !self.sort.dirs_first;
+        self.selection = 0;
+    }
+
+    /// Children of the current directory, in the current sort order.
+    pub fn current_children(&self) -> Vec<NodeId> {
+        let mut children = self.tree.children(self.current_dir).to_vec();
+        self.tree.sort(&mut children, self.sort, self.size_mode);
+        children
+    }
+
+    /// Browser columns currently on screen.
+    pub fn visible_columns(&self) -> &[Column] {
+        if self.show_columns {
+            &self.columns
+        } else {
+            &[]
+        }
+    }
+
+    pub fn toggle_columns(&mut self) {
+        self.show_columns = !self.show_columns;
+    }
+
+    fn selected_node(&self) -> Option<NodeId> {
+        self.current_children().get(self.selection).copied()
     }
 
+    /// Enters the selected directory. Mount points skipped by a
+    /// `--one-file-system` scan are walked on first entry.
     pub fn enter_dir(&mut self) {
-        let current_files = self.get_current_files();
-        if let Some(file) = current_files.get(self.selection) {
-            if file.is_dir {
-                self.current_path = file.path.clone();
+        if let Some(id) = self.selected_node() {
+            let node = self.tree.node(id);
+            if node.is_dir {
+                let unscanned_mount = node.is_mount_point && self.tree.children(id).is_empty();
+                self.current_dir = id;
+                self.current_path = self.tree.path(id);
                 self.selection = 0;
+                if unscanned_mount && !self.is_scanning && self.read_only_reason().is_none() {
+                    let path = self.current_path.clone();
+                    self.start_scan(&path);
+                }
             }
         }
     }
 
     pub fn go_up(&mut self) {
-        if let Some(parent) = self.current_path.parent() {
-            self.current_path = parent.to_path_buf();
+        if let Some(parent) = self.tree.parent(self.current_dir) {
+            self.current_dir = parent;
+            self.current_path = self.tree.path(parent);
             self.selection = 0;
         }
     }
 
     pub fn date_next(&mut self) {
-        let max = self.get_current_files().len();
+        let max = self.tree.children(self.current_dir).len();
         if max > 0 {
             self.selection = (self.selection + 1) % max;
         }
     }
 
     pub fn date_prev(&mut self) {
-        let max = self.get_current_files().len();
+        let max = self.tree.children(self.current_dir).len();
         if max > 0 {
             if self.selection == 0 {
                 self.selection = max - 1;
@@ -139,42 +476,115 @@ impl App {
         }
     }
 
-    pub fn get_current_files(&self) -> Vec<&FileStats> {
-        self.files
+    /// Children of the current directory that take up space, largest first.
+    pub fn treemap_children(&self) -> Vec<NodeId> {
+        let mut children: Vec<NodeId> = self
+            .tree
+            .children(self.current_dir)
             .iter()
-            .filter(|f| {
-                if let Some(parent) = f.path.parent() {
-                    parent == self.current_path
-                } else {
-                    false
-                }
-            })
+            .copied()
+            .filter(|&id| self.tree.node(id).size(self.size_mode) > 0)
+            .collect();
+        children.sort_by_key(|&id| std::cmp::Reverse(self.tree.node(id).size(self.size_mode)));
+        children
+    }
+
+    /// Treemap tiles of the current directory laid out in `area`.
+    pub fn treemap_layout(&self, area: Rect) -> Vec<(NodeId, Rect)> {
+        let children = self.treemap_children();
+        let sizes: Vec<u64> = children
+            .iter()
+            .map(|&id| self.tree.node(id).size(self.size_mode))
+            .collect();
+        children
+            .into_iter()
+            .zip(treemap::squarify(&sizes, area))
+            .collect()
+    }
+
+    /// Moves the treemap selection to the neighbouring tile in `area`.
+    pub fn treemap_move(&mut self, direction: Direction, area: Rect) {
+        let tiles: Vec<Rect> = self
+            .treemap_layout(area)
+            .into_iter()
+            .map(|(_, tile)| tile)
+            .collect();
+        if let Some(next) = treemap::neighbour(&tiles, self.treemap_selection, direction) {
+            self.treemap_selection = next;
+        }
+    }
+
+    /// Zooms into the selected tile if it is a directory.
+    pub fn treemap_enter(&mut self) {
+        let Some(&id) = self.treemap_children().get(self.treemap_selection) else {
+            return;
+        };
+        if let Some(index) = self.current_children().iter().position(|&c| c == id) {
+            self.selection = index;
+            self.enter_dir();
+            if self.current_dir == id {
+                self.treemap_selection = 0;
+            }
+        }
+    }
+
+    /// Zooms out, selecting the directory just left.
+    pub fn treemap_up(&mut self) {
+        let left = self.current_dir;
+        self.go_up();
+        self.treemap_selection = self
+            .treemap_children()
+            .iter()
+            .position(|&id| id == left)
+            .unwrap_or(0);
+    }
+
+    pub fn get_current_files(&self) -> Vec<FileStats> {
+        self.current_children()
+            .into_iter()
+            .map(|id| self.tree.stats(id))
             .collect()
     }
 
+    /// Asks to delete the highlighted entry, unless the safety rules
+    /// already forbid it.
     pub fn request_delete(&mut self) {
-        let current_files = self.get_current_files();
-        if let Some(file_stat) = current_files.get(self.selection) {
-            self.item_to_delete = Some(file_stat.path.clone());
+        if self.refuse_if_read_only("deleting") {
+            return;
+        }
+        if let Some(id) = self.selected_node() {
+            let path = self.tree.path(id);
+            if let Err(blocked) = self.check_deletable(&path) {
+                self.notice = Some(format!("{} not deleted: {}", path.display(), blocked));
+                return;
+            }
+            self.item_to_delete = Some(path);
             self.mode = AppMode::DeleteConfirmation;
         }
     }
 
     pub fn confirm_delete(&mut self) {
-        if let Some(path) = &self.item_to_delete {
-            if let Ok(freed_size) = self.cleaner.delete_item(path) {
-                let p = path.clone();
-                if let Some(index) = self.files.iter().position(|f| f.path == p) {
-                    self.files.remove(index);
-                    self.total_size = self.total_size.saturating_sub(freed_size);
-
-                    let new_len = self.get_current_files().len();
+        if self.refuse_if_read_only("deleting") {
+            self.item_to_delete = None;
+            self.mode = AppMode::Browsing;
+            return;
+        }
+        if let Some(path) = self.item_to_delete.clone() {
+            if !self.confirm_limits(self.disk_size(&path), 1) {
+                return;
+            }
+            self.cleaner.begin_operation();
+            match self.delete_path(&path, None) {
+                Ok(_) => {
+                    self.remove_entry(&path);
+                    self.last_removed = 1;
+
+                    let new_len = self.tree.children(self.current_dir).len();
                     if self.selection >= new_len && new_len > 0 {
                         self.selection = new_len - 1;
                     }
                 }
-            } else {
-                // TODO: Show error
+                Err(e) => self.notice = Some(format!("{} not deleted: {}", path.display(), e)),
             }
         }
         self.item_to_delete = None;
@@ -183,31 +593,164 @@ impl App {
 
     pub fn cancel_delete(&mut self) {
         self.item_to_delete = None;
+        self.over_limit = None;
         self.mode = AppMode::Browsing;
     }
 
+    /// Marks or unmarks the highlighted entry for batch deletion.
+    pub fn toggle_mark(&mut self) {
+        if let Some(id) = self.selected_node() {
+            let path = self.tree.path(id);
+            if !self.marked.remove(&path) {
+                self.marked.insert(path);
+            }
+        }
+    }
+
+    /// Marked paths with nothing marked above them; deleting these deletes
+    /// everything marked.
+    pub fn marked_roots(&self) -> Vec<PathBuf> {
+        self.marked
+            .iter()
+            .filter(|path| {
+                !path
+                    .ancestors()
+                    .skip(1)
+                    .any(|ancestor| self.marked.contains(ancestor))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Total size of the marked entries, counting nested marks once.
+    pub fn marked_size(&self) -> u64 {
+        self.marked_roots()
+            .iter()
+            .filter_map(|path| self.tree.find(path))
+            .map(|id| self.tree.node(id).size(self.size_mode))
+            .sum()
+    }
+
+    pub fn show_marked(&mut self) {
+        self.marked_selection = 0;
+        self.deletion_results.clear();
+        self.mode = AppMode::Marked;
+    }
+
+    pub fn marked_next(&mut self) {
+        if !self.marked.is_empty() {
+            self.marked_selection = (self.marked_selection + 1) % self.marked.len();
+        }
+    }
+
+    pub fn marked_prev(&mut self) {
+        if !self.marked.is_empty() {
+            if self.marked_selection == 0 {
+                self.marked_selection = self.marked.len() - 1;
+            } else {
+                self.marked_selection -= 1;
+            }
+        }
+    }
+
+    /// Unmarks the entry highlighted in the marked view.
+    pub fn unmark_selected(&mut self) {
+        if let Some(path) = self.marked.iter().nth(self.marked_selection).cloned() {
+            self.marked.remove(&path);
+            self.marked_selection = self
+                .marked_selection
+                .min(self.marked.len().saturating_sub(1));
+        }
+    }
+
+    pub fn request_batch_delete(&mut self) {
+        if !self.marked.is_empty() && !self.refuse_if_read_only("deleting") {
+            self.mode = AppMode::BatchDeleteConfirmation;
+        }
+    }
+
+    /// Deletes every marked entry, recording how each deletion went. Failed
+    /// entries stay marked.
+    pub fn confirm_batch_delete(&mut self) {
+        if self.refuse_if_read_only("deleting") {
+            self.mode = AppMode::Marked;
+            return;
+        }
+        let roots = self.marked_roots();
+        let bytes = roots.iter().map(|path| self.disk_size(path)).sum();
+        if !self.confirm_limits(bytes, roots.len()) {
+            return;
+        }
+        self.cleaner.begin_operation();
+        self.deletion_results = roots
+            .into_iter()
+            .map(|path| {
+                let outcome = self.delete_path(&path, None);
+                if outcome.is_ok() {
+                    self.remove_entry(&path);
+                }
+                DeletionResult { path, outcome }
+            })
+            .collect();
+        self.last_removed = self
+            .deletion_results
+            .iter()
+            .filter(|result| result.outcome.is_ok())
+            .count();
+        self.marked_selection = 0;
+        let len = self.tree.children(self.current_dir).len();
+        self.selection = self.selection.min(len.saturating_sub(1));
+        self.mode = AppMode::Marked;
+    }
+
+    pub fn cancel_batch_delete(&mut self) {
+        self.over_limit = None;
+        self.mode = AppMode::Marked;
+    }
+
+    /// Sum and count of the files (not directories) whose type matches.
+    /// Directory sizes already include their contents, so only files are summed.
+    fn file_totals(&self, matches: fn(&FileType) -> bool) -> (u64, usize) {
+        self.tree
+            .iter()
+            .map(|id| self.tree.node(id))
+            .filter(|node| !node.is_dir && matches(&node.file_type))
+            .fold((0, 0), |(size, count), node| {
+                (size + node.counted_size(self.size_mode), count + 1)
+            })
+    }
+
+    /// Paths of the outermost entries whose type matches; anything below them
+    /// goes with them when they are deleted.
+    fn outermost_matching(&self, matches: fn(&FileType) -> bool) -> Vec<PathBuf> {
+        self.tree
+            .iter()
+            .filter(|&id| matches(&self.tree.node(id).file_type))
+            .filter(|&id| {
+                let mut ancestor = self.tree.parent(id);
+                while let Some(parent) = ancestor {
+                    if matches(&self.tree.node(parent).file_type) {
+                        return false;
+                    }
+                    ancestor = self.tree.parent(parent);
+                }
+                true
+            })
+            .map(|id| self.tree.path(id))
+            .collect()
+    }
+
     pub fn scan_dashboard(&mut self) {
         self.recommendations.clear();
         self.recommendation_selection = 0;
 
         // 1. Logs
-        let log_size: u64 = self
-            .files
-            .iter()
-            .filter(|f| matches!(f.file_type, FileType::Log))
-            .map(|f| f.size)
-            .sum();
+        let (log_size, log_count) = self.file_totals(is_log);
 
         if log_size > 0 {
             self.recommendations.push(Recommendation {
                 category: RecommendationCategory::Log,
-                description: format!(
-                    "Log files found ({} files)",
-                    self.files
-                        .iter()
-                        .filter(|f| matches!(f.file_type, FileType::Log))
-                        .count()
-                ),
+                description: format!("Log files found ({} files)", log_count),
                 size: log_size,
                 path: None,
                 action_command: None,
@@ -215,20 +758,7 @@ impl App {
         }
 
         // 2. Caches
-        let cache_size: u64 = self
-            .files
-            .iter()
-            .filter(|f| {
-                matches!(
-                    f.file_type,
-                    FileType::Cache
-                        | FileType::NpmCache
-                        | FileType::ComposerCache
-                        | FileType::AptCache
-                )
-            })
-            .map(|f| f.size)
-            .sum();
+        let (cache_size, _) = self.file_totals(is_cache);
 
         if cache_size > 0 {
             self.recommendations.push(Recommendation {
@@ -241,14 +771,14 @@ impl App {
         }
 
         // 3. Docker (Use Analyzer)
-        let docker_file_size: u64 = self
-            .files
-            .iter()
-            .filter(|f| matches!(f.file_type, FileType::Docker))
-            .map(|f| f.size)
-            .sum();
+        let (docker_file_size, _) = self.file_totals(|t| matches!(t, FileType::Docker));
 
-        if let Ok(Some(rec)) = self.analyzer.analyze() {
+        // Docker reports bytes, which mean nothing when counting entries.
+        let analyzed = match self.size_mode {
+            SizeMode::Count => None,
+            SizeMode::Disk | SizeMode::Apparent => self.analyzer.analyze().ok().flatten(),
+        };
+        if let Some(rec) = analyzed {
             self.recommendations.push(rec);
         } else if docker_file_size > 0 {
             // Fallback
@@ -260,6 +790,42 @@ impl App {
                 action_command: None,
             });
         }
+
+        // 4. Directories full of small files, which exhaust inodes first
+        self.recommendations.extend(self.small_file_dirs());
+    }
+
+    /// Directories with at least `small_files_threshold` small files directly
+    /// inside them, most files first.
+    fn small_file_dirs(&self) -> Vec<Recommendation> {
+        let mut dirs: Vec<(NodeId, usize, u64)> = std::iter::once(self.tree.root())
+            .chain(self.tree.iter())
+            .filter(|&id| self.tree.node(id).is_dir)
+            .filter_map(|id| {
+                let (count, size) = self
+                    .tree
+                    .children(id)
+                    .iter()
+                    .map(|&child| self.tree.node(child))
+                    .filter(|node| !node.is_dir && node.apparent_size <= SMALL_FILE_SIZE)
+                    .fold((0, 0), |(count, size), node| {
+                        (count + 1, size + node.counted_size(self.size_mode))
+                    });
+                (count >= self.small_files_threshold).then_some((id, count, size))
+            })
+            .collect();
+        dirs.sort_by_key(|&(_, count, _)| std::cmp::Reverse(count));
+
+        dirs.into_iter()
+            .take(MAX_SMALL_FILE_DIRS)
+            .map(|(id, count, size)| Recommendation {
+                category: RecommendationCategory::SmallFiles,
+                description: format!("{} small files in {}", count, self.tree.path(id).display()),
+                size,
+                path: Some(self.tree.path(id)),
+                action_command: None,
+            })
+            .collect()
     }
 
     pub fn dashboard_next(&mut self) {
@@ -279,64 +845,68 @@ impl App {
         }
     }
 
+    /// Asks to clean the selected recommendation. Small-file directories are
+    /// not deleted wholesale; they open in the browser for a closer look.
     pub fn request_clean_recommendation(&mut self) {
-        if !self.recommendations.is_empty() {
+        if let Some(rec) = self.recommendations.get(self.recommendation_selection) {
+            if rec.category == RecommendationCategory::SmallFiles {
+                if let Some(id) = rec.path.as_deref().and_then(|p| self.tree.find(p)) {
+                    self.current_dir = id;
+                    self.current_path = self.tree.path(id);
+                    self.selection = 0;
+                    self.mode = AppMode::Browsing;
+                }
+                return;
+            }
+        }
+        if !self.recommendations.is_empty() && !self.refuse_if_read_only("cleaning up") {
             self.mode = AppMode::DashboardCleanupConfirmation;
         }
     }
 
     pub fn confirm_clean_recommendation(&mut self) {
+        if self.refuse_if_read_only("cleaning up") {
+            self.mode = AppMode::Dashboard;
+            return;
+        }
         if let Some(rec_ref) = self.recommendations.get(self.recommendation_selection) {
             let rec = rec_ref.clone();
             match rec.category {
                 RecommendationCategory::Docker => {
-                    if let Some(_cmd) = &rec.action_command {
-                        let _ = self.analyzer.prune();
+                    if !self.confirm_limits(rec.size, 1) {
+                        return;
+                    }
+                    if let Some(cmd) = &rec.action_command {
+                        let outcome = self
+                            .analyzer
+                            .prune()
+                            .map(|()| rec.size)
+                            .map_err(|e| e.to_string());
+                        self.audit(AuditEntry {
+                            target: cmd.clone(),
+                            size: Some(rec.size),
+                            file_type: Some(FileType::Docker),
+                            recommendation: Some(RecommendationCategory::Docker),
+                            removal: self.analyzer.removal(),
+                            outcome,
+                        });
                     }
                     self.scan_dashboard();
                 }
                 RecommendationCategory::Log => {
-                    let logs: Vec<PathBuf> = self
-                        .files
-                        .iter()
-                        .filter(|f| matches!(f.file_type, FileType::Log))
-                        .map(|f| f.path.clone())
-                        .collect();
-
-                    for path in logs {
-                        let _ = self.cleaner.delete_item(&path);
+                    let logs = self.outermost_matching(is_log);
+                    if !self.confirm_paths_limits(&logs) {
+                        return;
                     }
-                    self.files.retain(|f| !matches!(f.file_type, FileType::Log));
+                    self.delete_all(logs, RecommendationCategory::Log);
                     self.scan_dashboard();
                 }
                 RecommendationCategory::Cache => {
-                    let items_to_delete: Vec<PathBuf> = self
-                        .files
-                        .iter()
-                        .filter(|f| {
-                            matches!(
-                                f.file_type,
-                                FileType::Cache
-                                    | FileType::NpmCache
-                                    | FileType::ComposerCache
-                                    | FileType::AptCache
-                            )
-                        })
-                        .map(|f| f.path.clone())
-                        .collect();
-
-                    for path in items_to_delete {
-                        let _ = self.cleaner.delete_item(&path);
+                    let items_to_delete = self.outermost_matching(is_cache);
+                    if !self.confirm_paths_limits(&items_to_delete) {
+                        return;
                     }
-                    self.files.retain(|f| {
-                        !matches!(
-                            f.file_type,
-                            FileType::Cache
-                                | FileType::NpmCache
-                                | FileType::ComposerCache
-                                | FileType::AptCache
-                        )
-                    });
+                    self.delete_all(items_to_delete, RecommendationCategory::Cache);
                     self.scan_dashboard();
                 }
                 _ => {}
@@ -345,7 +915,390 @@ impl App {
         self.mode = AppMode::Dashboard;
     }
 
+    /// Why this session must leave the disk alone: imports and snapshots
+    /// show paths from another machine or an older scan.
+    pub fn read_only_reason(&self) -> Option<String> {
+        if let Some(file) = &self.import {
+            return Some(format!("browsing the import {}", file.display()));
+        }
+        self.snapshot
+            .as_ref()
+            .map(|meta| format!("browsing a saved snapshot of {}", meta.root.display()))
+    }
+
+    /// Refuses `what` in a read-only session, saying why in the notice.
+    /// Returns whether it was refused.
+    fn refuse_if_read_only(&mut self, what: &str) -> bool {
+        let Some(reason) = self.read_only_reason() else {
+            return false;
+        };
+        self.notice = Some(format!("Read-only: no {} while {}", what, reason));
+        true
+    }
+
+    /// Whether a dry run has pretended to delete parts of the tree, so it no
+    /// longer matches the disk.
+    pub fn is_simulated(&self) -> bool {
+        self.dry_run
+            .as_ref()
+            .is_some_and(|log| !log.entries().is_empty())
+    }
+
+    /// Runs every dashboard cleanup in turn, as if each were confirmed,
+    /// to see what the cleanups would remove. Does nothing outside dry runs.
+    pub fn clean_all_recommendations(&mut self) {
+        if self.dry_run.is_none() || self.refuse_if_read_only("cleaning up") {
+            return;
+        }
+        self.scan_dashboard();
+        let categories: Vec<RecommendationCategory> = self
+            .recommendations
+            .iter()
+            .map(|rec| rec.category.clone())
+            .collect();
+        for category in categories {
+            if let Some(index) = self
+                .recommendations
+                .iter()
+                .position(|rec| rec.category == category)
+            {
+                self.recommendation_selection = index;
+                self.confirm_clean_recommendation();
+                // Only recorded, so going past the limits is safe here.
+                if self.over_limit.is_some() {
+                    self.confirm_clean_recommendation();
+                }
+            }
+        }
+        self.recommendation_selection = 0;
+    }
+
+    /// Deletes every path and drops the deleted ones from the tree. Paths
+    /// that could not be deleted are counted in the notice.
+    fn delete_all(&mut self, paths: Vec<PathBuf>, recommendation: RecommendationCategory) {
+        self.cleaner.begin_operation();
+        self.last_removed = 0;
+        let mut failures = Vec::new();
+        for path in paths {
+            match self.delete_path(&path, Some(recommendation.clone())) {
+                Ok(_) => {
+                    self.last_removed += 1;
+                    self.remove_entry(&path);
+                }
+                Err(e) => failures.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+        if let Some(first) = failures.first() {
+            self.notice = Some(format!(
+                "{} of {} items not deleted; first: {}",
+                failures.len(),
+                failures.len() + self.last_removed,
+                first
+            ));
+        }
+    }
+
+    /// Asks for a second confirmation when an operation would remove more
+    /// than the safety limits allow. Returns whether to go ahead now.
+    fn confirm_limits(&mut self, bytes: u64, items: usize) -> bool {
+        if self.over_limit.take().is_some() || !self.safety.exceeds_limits(bytes, items) {
+            return true;
+        }
+        self.over_limit = Some((bytes, items));
+        false
+    }
+
+    fn confirm_paths_limits(&mut self, paths: &[PathBuf]) -> bool {
+        let bytes = paths.iter().map(|path| self.disk_size(path)).sum();
+        self.confirm_limits(bytes, paths.len())
+    }
+
+    fn disk_size(&self, path: &Path) -> u64 {
+        self.tree
+            .find(path)
+            .map_or(0, |id| self.tree.node(id).size(SizeMode::Disk))
+    }
+
+    /// Checks `path` against the safety rules, including protected names
+    /// anywhere below it in the tree.
+    pub fn check_deletable(&self, path: &Path) -> Result<(), Blocked> {
+        self.safety.check(self.tree.root_path(), path)?;
+        let Some(id) = self.tree.find(path) else {
+            return Ok(());
+        };
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            for &child in self.tree.children(id) {
+                if self
+                    .safety
+                    .is_protected_name(&self.tree.name(child).to_string_lossy())
+                {
+                    return Err(Blocked::Protected(self.tree.path(child)));
+                }
+                stack.push(child);
+            }
+        }
+        Ok(())
+    }
+
+    /// Deletes `path` with the cleaner and writes the attempt to the audit
+    /// log, along with what the scan knew about it.
+    fn delete_path(
+        &mut self,
+        path: &Path,
+        recommendation: Option<RecommendationCategory>,
+    ) -> Result<u64, String> {
+        let node = self.tree.find(path).map(|id| self.tree.node(id));
+        let size = node.map(|node| node.size(SizeMode::Disk));
+        let file_type = node.map(|node| node.file_type.clone());
+        let outcome = match self.check_deletable(path) {
+            Ok(()) => self.cleaner.delete_item(path).map_err(|e| e.to_string()),
+            Err(blocked) => Err(blocked.to_string()),
+        };
+        self.audit(AuditEntry {
+            target: path.display().to_string(),
+            size,
+            file_type,
+            recommendation,
+            removal: self.cleaner.removal(),
+            outcome: outcome.clone(),
+        });
+        outcome
+    }
+
+    /// Appends `entry` to the audit log. Dry runs remove nothing, so they
+    /// stay out of it; their plan is in the dry-run summary instead.
+    fn audit(&mut self, entry: AuditEntry) {
+        if entry.removal == Removal::DryRun {
+            return;
+        }
+        if let Some(log) = &self.audit {
+            if let Err(e) = log.append(entry) {
+                self.notice = Some(format!("Audit log failed: {}", e));
+            }
+        }
+    }
+
+    /// Loads the audit log, newest first, and shows it.
+    pub fn show_audit(&mut self) {
+        self.audit_records = match &self.audit {
+            Some(log) => match log.records() {
+                Ok(records) => records.into_iter().rev().collect(),
+                Err(e) => {
+                    self.notice = Some(format!("Audit log failed: {}", e));
+                    Vec::new()
+                }
+            },
+            None => Vec::new(),
+        };
+        self.audit_selection = 0;
+        self.mode = AppMode::Audit;
+    }
+
+    pub fn audit_next(&mut self) {
+        if !self.audit_records.is_empty() {
+            self.audit_selection = (self.audit_selection + 1) % self.audit_records.len();
+        }
+    }
+
+    pub fn audit_prev(&mut self) {
+        if !self.audit_records.is_empty() {
+            if self.audit_selection == 0 {
+                self.audit_selection = self.audit_records.len() - 1;
+            } else {
+                self.audit_selection -= 1;
+            }
+        }
+    }
+
+    /// Restores what the most recent delete, batch or cleanup removed, even
+    /// one from an earlier session, and rescans where it went back to.
+    pub fn undo_last(&mut self) {
+        if self.refuse_if_read_only("undoing") {
+            return;
+        }
+        match self.cleaner.undo(1) {
+            Ok(restored) if restored.is_empty() => {
+                self.notice = Some("Nothing to undo".to_string());
+            }
+            Ok(restored) => {
+                self.notice = Some(format!("Restored {} item(s)", restored.len()));
+                self.rescan_around(&restored);
+            }
+            Err(e) => self.notice = Some(format!("Undo failed: {}", e)),
+        }
+        self.last_removed = 0;
+    }
+
+    /// Rescans the deepest directory in the tree holding all of `paths`.
+    fn rescan_around(&mut self, paths: &[PathBuf]) {
+        if self.is_scanning || self.scanner.is_none() {
+            return;
+        }
+        let mut common: Option<PathBuf> = None;
+        for parent in paths.iter().filter_map(|path| path.parent()) {
+            common = Some(match common {
+                None => parent.to_path_buf(),
+                Some(common) => common
+                    .components()
+                    .zip(parent.components())
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _)| a)
+                    .collect(),
+            });
+        }
+        let Some(id) = common
+            .as_deref()
+            .and_then(|common| common.ancestors().find_map(|dir| self.tree.find(dir)))
+        else {
+            return;
+        };
+        self.rescan_node(id);
+    }
+
     pub fn cancel_clean(&mut self) {
+        self.over_limit = None;
         self.mode = AppMode::Dashboard;
     }
+
+    /// Shows the comparison of two scans, starting at their roots.
+    pub fn open_diff(&mut self, diff: TreeDiff) {
+        self.current_path = diff.new_tree().root_path().to_path_buf();
+        self.diff = Some(diff);
+        self.diff_path = PathBuf::new();
+        self.diff_selection = 0;
+        self.mode = AppMode::Diff;
+    }
+
+    /// Changed entries of the current diff directory, sorted by delta.
+    pub fn diff_entries(&self) -> Vec<DiffEntry> {
+        let Some(diff) = &self.diff else {
+            return Vec::new();
+        };
+        let mut entries = diff.children(&self.diff_path, self.size_mode);
+        entries.sort_by(|a, b| match self.sort.order {
+            SortOrder::Desc => b.delta().cmp(&a.delta()),
+            SortOrder::Asc => a.delta().cmp(&b.delta()),
+        });
+        entries
+    }
+
+    pub fn diff_next(&mut self) {
+        let max = self.diff_entries().len();
+        if max > 0 {
+            self.diff_selection = (self.diff_selection + 1) % max;
+        }
+    }
+
+    pub fn diff_prev(&mut self) {
+        let max = self.diff_entries().len();
+        if max > 0 {
+            if self.diff_selection == 0 {
+                self.diff_selection = max - 1;
+            } else {
+                self.diff_selection -= 1;
+            }
+        }
+    }
+
+    pub fn diff_enter(&mut self) {
+        if let Some(entry) = self.diff_entries().get(self.diff_selection) {
+            if entry.is_dir {
+                self.diff_path = entry.path.clone();
+                self.diff_selection = 0;
+                self.update_diff_path();
+            }
+        }
+    }
+
+    pub fn diff_up(&mut self) {
+        if self.diff_path.pop() {
+            self.diff_selection = 0;
+            self.update_diff_path();
+        }
+    }
+
+    fn update_diff_path(&mut self) {
+        if let Some(diff) = &self.diff {
+            self.current_path = diff.new_tree().root_path().join(&self.diff_path);
+        }
+    }
+
+    /// Shows the mounted filesystems, refreshed from the mount lister.
+    pub fn show_filesystems(&mut self) {
+        if let Some(mounts) = &self.mounts {
+            self.filesystems = mounts.filesystems();
+        }
+        self.filesystem_selection = self
+            .filesystem_selection
+            .min(self.filesystems.len().saturating_sub(1));
+        self.mode = AppMode::Filesystems;
+    }
+
+    pub fn filesystems_next(&mut self) {
+        if !self.filesystems.is_empty() {
+            self.filesystem_selection = (self.filesystem_selection + 1) % self.filesystems.len();
+        }
+    }
+
+    pub fn filesystems_prev(&mut self) {
+        if !self.filesystems.is_empty() {
+            if self.filesystem_selection == 0 {
+                self.filesystem_selection = self.filesystems.len() - 1;
+            } else {
+                self.filesystem_selection -= 1;
+            }
+        }
+    }
+
+    /// Leaves the overview for the scan results, if there are any yet.
+    pub fn close_filesystems(&mut self) {
+        if self.is_scanning && self.scan_handle.is_some() {
+            self.mode = AppMode::Scanning;
+        } else if !self.tree.is_empty() {
+            self.mode = AppMode::Browsing;
+        }
+    }
+
+    /// Starts a fresh scan of the selected filesystem's mount point.
+    pub fn scan_selected_filesystem(&mut self) {
+        if let Some(fs) = self.filesystems.get(self.filesystem_selection) {
+            let root = fs.mount_point.clone();
+            self.scan_new_root(&root);
+        }
+    }
+
+    /// Drops the current results and scans `root` from scratch. Imports and
+    /// snapshots have no scanner for the disk, so they stay as they are.
+    pub fn scan_new_root(&mut self, root: &Path) {
+        if self.refuse_if_read_only("scanning another filesystem") {
+            return;
+        }
+        if let Some(handle) = self.scan_handle.take() {
+            handle.cancel();
+        }
+        self.tree = DirTree::new(root);
+        self.current_dir = self.tree.root();
+        self.current_path = root.to_path_buf();
+        self.selection = 0;
+        self.errors.clear();
+        self.error_selection = 0;
+        self.progress = ScanProgress::default();
+        self.recommendations.clear();
+        self.history.clear();
+        self.marked.clear();
+        self.mode = AppMode::Scanning;
+        self.start_scan(root);
+    }
+}
+
+fn is_log(file_type: &FileType) -> bool {
+    matches!(file_type, FileType::Log)
+}
+
+fn is_cache(file_type: &FileType) -> bool {
+    matches!(
+        file_type,
+        FileType::Cache | FileType::NpmCache | FileType::ComposerCache | FileType::AptCache
+    )
 }
@@ -0,0 +1,131 @@
+use crate::domain::entities::SizeMode;
+use crate::domain::tree::{DirTree, NodeId};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    Added,
+    Removed,
+    Grown,
+    Shrunk,
+    Unchanged,
+}
+
+impl DiffKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            DiffKind::Added => "added",
+            DiffKind::Removed => "removed",
+            DiffKind::Grown => "grown",
+            DiffKind::Shrunk => "shrunk",
+            DiffKind::Unchanged => "unchanged",
+        }
+    }
+}
+
+/// An entry compared between two scans. Sizes are `None` where the entry
+/// does not exist in that scan.
+#[derive(Debug, Clone)]
+pub struct DiffEntry {
+    /// Path relative to the scan roots.
+    pub path: PathBuf,
+    pub old_size: Option<u64>,
+    pub new_size: Option<u64>,
+    pub is_dir: bool,
+}
+
+impl DiffEntry {
+    pub fn kind(&self) -> DiffKind {
+        match (self.old_size, self.new_size) {
+            (None, _) => DiffKind::Added,
+            (_, None) => DiffKind::Removed,
+            (Some(old), Some(new)) if new > old => DiffKind::Grown,
+            (Some(old), Some(new)) if new < old => DiffKind::Shrunk,
+            _ => DiffKind::Unchanged,
+        }
+    }
+
+    /// Change in bytes from the old scan to the new one.
+    pub fn delta(&self) -> i64 {
+        self.new_size.unwrap_or(0) as i64 - self.old_size.unwrap_or(0) as i64
+    }
+}
+
+/// Compares two scans of the same directory, one directory at a time.
+/// Entries are matched by their path relative to each scan's root.
+#[derive(Debug)]
+pub struct TreeDiff {
+    old: DirTree,
+    new: DirTree,
+}
+
+impl TreeDiff {
+    pub fn new(old: DirTree, new: DirTree) -> Self {
+        Self { old, new }
+    }
+
+    pub fn old(&self) -> &DirTree {
+        &self.old
+    }
+
+    pub fn new_tree(&self) -> &DirTree {
+        &self.new
+    }
+
+    /// The entry at `relative`; the empty path is the root.
+    pub fn entry(&self, relative: &Path, mode: SizeMode) -> DiffEntry {
+        let old = find(&self.old, relative);
+        let new = find(&self.new, relative);
+        self.compare(relative.to_path_buf(), old, new, mode)
+    }
+
+    /// Entries directly below `relative` that changed between the scans.
+    pub fn children(&self, relative: &Path, mode: SizeMode) -> Vec<DiffEntry> {
+        let mut pairs: BTreeMap<OsString, (Option<NodeId>, Option<NodeId>)> = BTreeMap::new();
+        if let Some(dir) = find(&self.old, relative) {
+            for &child in self.old.children(dir) {
+                pairs
+                    .entry(self.old.name(child).to_os_string())
+                    .or_default()
+                    .0 = Some(child);
+            }
+        }
+        if let Some(dir) = find(&self.new, relative) {
+            for &child in self.new.children(dir) {
+                pairs
+                    .entry(self.new.name(child).to_os_string())
+                    .or_default()
+                    .1 = Some(child);
+            }
+        }
+
+        pairs
+            .into_iter()
+            .map(|(name, (old, new))| self.compare(relative.join(name), old, new, mode))
+            .filter(|entry| entry.kind() != DiffKind::Unchanged)
+            .collect()
+    }
+
+    fn compare(
+        &self,
+        path: PathBuf,
+        old: Option<NodeId>,
+        new: Option<NodeId>,
+        mode: SizeMode,
+    ) -> DiffEntry {
+        let old = old.map(|id| self.old.node(id));
+        let new = new.map(|id| self.new.node(id));
+        DiffEntry {
+            path,
+            old_size: old.map(|node| node.size(mode)),
+            new_size: new.map(|node| node.size(mode)),
+            is_dir: new.or(old).is_some_and(|node| node.is_dir),
+        }
+    }
+}
+
+fn find(tree: &DirTree, relative: &Path) -> Option<NodeId> {
+    tree.find(&tree.root_path().join(relative))
+}
@@ -1,5 +1,6 @@
+use std::io;
 use std::path::PathBuf;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FileType {
@@ -12,14 +13,107 @@ pub enum FileType {
     Docker,
 }
 
+impl FileType {
+    /// Stable snake_case name, used in reports and stored history.
+    pub fn key(&self) -> &'static str {
+        match self {
+            FileType::Normal => "normal",
+            FileType::Log => "log",
+            FileType::Cache => "cache",
+            FileType::NpmCache => "npm_cache",
+            FileType::ComposerCache => "composer_cache",
+            FileType::AptCache => "apt_cache",
+            FileType::Docker => "docker",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<FileType> {
+        [
+            FileType::Normal,
+            FileType::Log,
+            FileType::Cache,
+            FileType::NpmCache,
+            FileType::ComposerCache,
+            FileType::AptCache,
+            FileType::Docker,
+        ]
+        .into_iter()
+        .find(|file_type| file_type.key() == key)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FileStats {
     pub path: PathBuf,
+    /// Apparent size in bytes, as reported by the file length.
     pub size: u64,
+    /// Bytes actually allocated on disk (`st_blocks * 512` on Unix).
+    pub allocated_size: u64,
     pub is_dir: bool,
     pub modified: SystemTime,
     pub children_count: Option<usize>,
     pub file_type: FileType,
+    /// Number of hard links to the underlying inode.
+    pub links: u64,
+    /// Set when the inode was already counted under another path, so its
+    /// bytes are left out of directory totals.
+    pub duplicate_inode: bool,
+    /// Set for directories on a different device than their parent.
+    pub is_mount_point: bool,
+    /// (device, inode) identity of hard-linked files, so exports can keep
+    /// their links together. `None` for everything else.
+    pub inode: Option<(u64, u64)>,
+}
+
+impl FileStats {
+    pub fn is_hardlink(&self) -> bool {
+        !self.is_dir && self.links > 1
+    }
+
+    pub fn size_for(&self, mode: SizeMode) -> u64 {
+        match mode {
+            SizeMode::Disk => self.allocated_size,
+            SizeMode::Apparent => self.size,
+            SizeMode::Count => 1 + self.children_count.unwrap_or(0) as u64,
+        }
+    }
+}
+
+/// Which size drives sorting, totals and recommendations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizeMode {
+    /// Allocated disk usage, like `du`.
+    #[default]
+    Disk,
+    /// File length, like `du --apparent-size`.
+    Apparent,
+    /// Number of entries, like `du --inodes`; finds inode hogs.
+    Count,
+}
+
+impl SizeMode {
+    pub fn toggle(self) -> Self {
+        match self {
+            SizeMode::Disk => SizeMode::Apparent,
+            SizeMode::Apparent | SizeMode::Count => SizeMode::Disk,
+        }
+    }
+
+    /// Switches between counting entries and disk usage.
+    pub fn toggle_count(self) -> Self {
+        match self {
+            SizeMode::Count => SizeMode::Disk,
+            SizeMode::Disk | SizeMode::Apparent => SizeMode::Count,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SizeMode::Disk => "disk usage",
+            SizeMode::Apparent => "apparent size",
+            SizeMode::Count => "entry count",
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -28,9 +122,134 @@ pub enum RecommendationCategory {
     Log,
     Cache,
     Trash,
+    /// A directory holding an excessive number of small files.
+    SmallFiles,
     Other,
 }
 
+impl RecommendationCategory {
+    pub const ALL: [RecommendationCategory; 6] = [
+        RecommendationCategory::Docker,
+        RecommendationCategory::Log,
+        RecommendationCategory::Cache,
+        RecommendationCategory::Trash,
+        RecommendationCategory::SmallFiles,
+        RecommendationCategory::Other,
+    ];
+
+    /// Stable snake_case name, used in reports and the audit log.
+    pub fn key(&self) -> &'static str {
+        match self {
+            RecommendationCategory::Docker => "docker",
+            RecommendationCategory::Log => "log",
+            RecommendationCategory::Cache => "cache",
+            RecommendationCategory::Trash => "trash",
+            RecommendationCategory::SmallFiles => "small_files",
+            RecommendationCategory::Other => "other",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<RecommendationCategory> {
+        Self::ALL.into_iter().find(|category| category.key() == key)
+    }
+}
+
+/// What deleting an item does to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Removal {
+    /// Removed for good.
+    Permanent,
+    /// Moved to the desktop trash.
+    Trash,
+    /// Moved aside so `undo` can put it back until it is purged.
+    Quarantine,
+    /// Left in place; a dry run only records it.
+    DryRun,
+    /// Removed for good from the quarantine, once the purge policy no
+    /// longer keeps it.
+    Purge,
+}
+
+impl Removal {
+    pub const ALL: [Removal; 5] = [
+        Removal::Permanent,
+        Removal::Trash,
+        Removal::Quarantine,
+        Removal::DryRun,
+        Removal::Purge,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Removal::Permanent => "permanent",
+            Removal::Trash => "trash",
+            Removal::Quarantine => "quarantine",
+            Removal::DryRun => "dry_run",
+            Removal::Purge => "purge",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Removal> {
+        Self::ALL.into_iter().find(|removal| removal.key() == key)
+    }
+}
+
+/// One deletion or prune, as written to the audit log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    /// The deleted path, or the prune command.
+    pub target: String,
+    /// Size of the target when it was scanned, if known.
+    pub size: Option<u64>,
+    pub file_type: Option<FileType>,
+    /// Dashboard recommendation that triggered it; `None` for deletions
+    /// picked in the browser.
+    pub recommendation: Option<RecommendationCategory>,
+    pub removal: Removal,
+    /// Bytes freed, or why it failed.
+    pub outcome: Result<u64, String>,
+}
+
+/// An audit entry stamped with when and by whom.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRecord {
+    pub time: SystemTime,
+    pub user: String,
+    pub entry: AuditEntry,
+}
+
+/// Something a dry run would have removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedRemoval {
+    /// A path, or a description such as a prune command.
+    pub target: String,
+    /// Disk usage it would have freed.
+    pub allocated_size: u64,
+    /// File length it would have freed.
+    pub apparent_size: u64,
+    /// Entries it would have removed.
+    pub entries: u64,
+}
+
+impl PlannedRemoval {
+    /// What it would have freed, measured the way `mode` measures the tree.
+    pub fn size(&self, mode: SizeMode) -> u64 {
+        match mode {
+            SizeMode::Disk => self.allocated_size,
+            SizeMode::Apparent => self.apparent_size,
+            SizeMode::Count => self.entries,
+        }
+    }
+}
+
+/// Outcome of deleting one entry of a batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeletionResult {
+    pub path: PathBuf,
+    /// Bytes freed, or why the deletion failed.
+    pub outcome: Result<u64, String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Recommendation {
     pub category: RecommendationCategory,
@@ -45,3 +264,303 @@ pub enum SortOrder {
     Desc,
     Asc,
 }
+
+impl SortOrder {
+    pub fn reverse(self) -> Self {
+        match self {
+            SortOrder::Desc => SortOrder::Asc,
+            SortOrder::Asc => SortOrder::Desc,
+        }
+    }
+}
+
+/// What entries are sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Size,
+    Name,
+    Modified,
+    /// Number of entries below a directory.
+    Count,
+    /// `FileType`, e.g. logs and caches together.
+    Type,
+    Extension,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 6] = [
+        SortKey::Size,
+        SortKey::Name,
+        SortKey::Modified,
+        SortKey::Count,
+        SortKey::Type,
+        SortKey::Extension,
+    ];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            SortKey::Size => "size",
+            SortKey::Name => "name",
+            SortKey::Modified => "modified",
+            SortKey::Count => "count",
+            SortKey::Type => "type",
+            SortKey::Extension => "extension",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<SortKey> {
+        SortKey::ALL
+            .into_iter()
+            .find(|sort_key| sort_key.key() == key)
+    }
+
+    /// The key after this one, wrapping around.
+    pub fn next(self) -> SortKey {
+        let index = SortKey::ALL.iter().position(|&k| k == self).unwrap_or(0);
+        SortKey::ALL[(index + 1) % SortKey::ALL.len()]
+    }
+
+    /// Biggest, newest and fullest first; names and types alphabetically.
+    pub fn default_order(self) -> SortOrder {
+        match self {
+            SortKey::Size | SortKey::Modified | SortKey::Count => SortOrder::Desc,
+            SortKey::Name | SortKey::Type | SortKey::Extension => SortOrder::Asc,
+        }
+    }
+}
+
+/// How the browser and reports order entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortSpec {
+    pub key: SortKey,
+    pub order: SortOrder,
+    /// List directories before files, whatever the key.
+    pub dirs_first: bool,
+}
+
+impl SortSpec {
+    /// Sorts by `key` in its natural order.
+    pub fn new(key: SortKey) -> Self {
+        SortSpec {
+            key,
+            order: key.default_order(),
+            dirs_first: false,
+        }
+    }
+
+    /// Short description for the header, e.g. `size desc, dirs first`.
+    pub fn label(&self) -> String {
+        let order = match self.order {
+            SortOrder::Desc => "desc",
+            SortOrder::Asc => "asc",
+        };
+        let dirs = if self.dirs_first { ", dirs first" } else { "" };
+        format!("{} {}{}", self.key.key(), order, dirs)
+    }
+}
+
+impl Default for SortSpec {
+    fn default() -> Self {
+        SortSpec::new(SortKey::Size)
+    }
+}
+
+/// Optional columns of the file browser, shown between size and name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    /// Bar proportional to the share of the parent directory.
+    Bar,
+    /// Percentage of the parent directory.
+    Percent,
+    /// Number of entries below a directory.
+    Count,
+    /// Last modification time.
+    Modified,
+}
+
+impl Column {
+    pub const ALL: [Column; 4] = [
+        Column::Bar,
+        Column::Percent,
+        Column::Count,
+        Column::Modified,
+    ];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            Column::Bar => "bar",
+            Column::Percent => "percent",
+            Column::Count => "count",
+            Column::Modified => "modified",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Column> {
+        Column::ALL.into_iter().find(|column| column.key() == key)
+    }
+}
+
+/// Options controlling how a scanner walks the filesystem.
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    /// Do not descend into directories on other filesystems, like `du -x`.
+    pub one_file_system: bool,
+    /// Glob patterns of entries to skip, matched against the file name, the
+    /// path relative to the scan root, and the full path.
+    pub excludes: Vec<String>,
+    /// Honor `.gitignore`, `.ignore` and `.diskdocignore` files found during the walk.
+    pub use_ignore_files: bool,
+}
+
+/// Describes a saved scan snapshot.
+#[derive(Debug, Clone)]
+pub struct SnapshotMeta {
+    pub root: PathBuf,
+    pub created: SystemTime,
+    pub host: String,
+    pub options: ScanOptions,
+    pub entries: usize,
+    pub apparent_size: u64,
+    pub allocated_size: u64,
+    pub errors: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanErrorKind {
+    PermissionDenied,
+    /// The entry disappeared between listing and reading it.
+    Vanished,
+    Io,
+    Other,
+}
+
+impl ScanErrorKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            ScanErrorKind::PermissionDenied => "permission denied",
+            ScanErrorKind::Vanished => "vanished",
+            ScanErrorKind::Io => "I/O error",
+            ScanErrorKind::Other => "error",
+        }
+    }
+}
+
+/// An entry the scanner could not read.
+#[derive(Debug, Clone)]
+pub struct ScanError {
+    pub path: Option<PathBuf>,
+    pub kind: ScanErrorKind,
+    /// OS error number, when the error came from the OS.
+    pub errno: Option<i32>,
+    pub message: String,
+}
+
+impl ScanError {
+    pub fn from_io(path: Option<PathBuf>, err: &io::Error) -> Self {
+        let kind = match err.kind() {
+            io::ErrorKind::PermissionDenied => ScanErrorKind::PermissionDenied,
+            io::ErrorKind::NotFound => ScanErrorKind::Vanished,
+            _ => ScanErrorKind::Io,
+        };
+        ScanError {
+            path,
+            kind,
+            errno: err.raw_os_error(),
+            message: err.to_string(),
+        }
+    }
+
+    pub fn other(path: Option<PathBuf>, message: impl Into<String>) -> Self {
+        ScanError {
+            path,
+            kind: ScanErrorKind::Other,
+            errno: None,
+            message: message.into(),
+        }
+    }
+}
+
+/// Snapshot of a running scan, sent periodically by the scanner.
+#[derive(Debug, Clone, Default)]
+pub struct ScanProgress {
+    pub bytes_seen: u64,
+    pub entries_seen: usize,
+    pub current_dir: Option<PathBuf>,
+    pub errors: usize,
+    /// Entries skipped by exclude patterns or ignore files.
+    pub excluded: usize,
+    pub elapsed: Duration,
+    /// Used bytes of the filesystem being scanned, when the scan covers it
+    /// entirely. Drives the percentage and ETA estimates.
+    pub expected_bytes: Option<u64>,
+}
+
+impl ScanProgress {
+    /// Bytes scanned per second so far.
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.bytes_seen as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Estimated completion in percent, capped at 100.
+    pub fn percent(&self) -> Option<u16> {
+        let expected = self.expected_bytes.filter(|&e| e > 0)?;
+        let ratio = self.bytes_seen as f64 / expected as f64;
+        Some((ratio * 100.0).min(100.0) as u16)
+    }
+
+    /// Estimated time remaining at the current throughput.
+    pub fn eta(&self) -> Option<Duration> {
+        let expected = self.expected_bytes?;
+        let throughput = self.throughput();
+        if throughput <= 0.0 {
+            return None;
+        }
+        let remaining = expected.saturating_sub(self.bytes_seen) as f64;
+        Some(Duration::from_secs_f64(remaining / throughput))
+    }
+}
+
+/// A mounted filesystem, as listed on the filesystem overview.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filesystem {
+    pub device: String,
+    pub mount_point: PathBuf,
+    pub fs_type: String,
+    pub options: Vec<String>,
+    pub total: u64,
+    pub available: u64,
+    /// Inode counts, where the filesystem reports them.
+    pub inodes_total: Option<u64>,
+    pub inodes_free: Option<u64>,
+}
+
+impl Filesystem {
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+
+    /// Share of the space in use, from 0.0 to 1.0.
+    pub fn usage(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.used() as f64 / self.total as f64
+        }
+    }
+
+    /// Share of the inodes in use, where known.
+    pub fn inode_usage(&self) -> Option<f64> {
+        let total = self.inodes_total.filter(|&t| t > 0)?;
+        let used = total.saturating_sub(self.inodes_free.unwrap_or(0));
+        Some(used as f64 / total as f64)
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.options.iter().any(|option| option == "ro")
+    }
+}
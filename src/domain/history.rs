@@ -0,0 +1,115 @@
+use crate::domain::entities::SizeMode;
+use crate::domain::tree::DirTree;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+const SECS_PER_DAY: f64 = 86_400.0;
+
+/// Summary of one completed scan, kept to chart usage over time.
+/// Sizes are disk usage.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryRecord {
+    pub root: PathBuf,
+    pub time: SystemTime,
+    pub size: u64,
+    pub entries: usize,
+    /// Size of each direct child of the root, by name.
+    pub top_level: BTreeMap<String, u64>,
+    /// Size of files per `FileType`, keyed by `FileType::key`.
+    pub file_types: BTreeMap<String, u64>,
+    /// Capacity and free space of the filesystem holding the root.
+    pub filesystem: Option<FilesystemSpace>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FilesystemSpace {
+    pub total: u64,
+    pub available: u64,
+}
+
+impl HistoryRecord {
+    pub fn from_tree(tree: &DirTree, filesystem: Option<FilesystemSpace>) -> Self {
+        let mode = SizeMode::Disk;
+        let top_level = tree
+            .children(tree.root())
+            .iter()
+            .map(|&id| {
+                (
+                    tree.name(id).to_string_lossy().into_owned(),
+                    tree.node(id).size(mode),
+                )
+            })
+            .collect();
+        let mut file_types = BTreeMap::new();
+        for id in tree.iter() {
+            let node = tree.node(id);
+            if !node.is_dir {
+                *file_types
+                    .entry(node.file_type.key().to_string())
+                    .or_insert(0) += node.counted_size(mode);
+            }
+        }
+
+        HistoryRecord {
+            root: tree.root_path().to_path_buf(),
+            time: SystemTime::now(),
+            size: tree.node(tree.root()).size(mode),
+            entries: tree.len(),
+            top_level,
+            file_types,
+            filesystem,
+        }
+    }
+}
+
+/// Growth of a path across its history records.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trend {
+    /// Least-squares growth rate, in bytes per day.
+    pub growth_per_day: f64,
+    /// When the filesystem fills up if the path keeps growing at this rate.
+    /// `None` when it is not growing, free space is unknown, or filling up
+    /// would take longer than a `Duration` can hold.
+    pub time_to_full: Option<Duration>,
+}
+
+/// Fits a line through the sizes of `records`, which must be sorted by time.
+/// Needs at least two records taken at different times.
+pub fn trend(records: &[HistoryRecord]) -> Option<Trend> {
+    let first = records.first()?.time;
+    let points: Vec<(f64, f64)> = records
+        .iter()
+        .map(|r| {
+            let days = r
+                .time
+                .duration_since(first)
+                .unwrap_or_default()
+                .as_secs_f64()
+                / SECS_PER_DAY;
+            (days, r.size as f64)
+        })
+        .collect();
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let var_x: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    if var_x == 0.0 {
+        return None;
+    }
+    let cov: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let growth_per_day = cov / var_x;
+
+    let available = records.last()?.filesystem.map(|fs| fs.available);
+    let time_to_full = match available {
+        // Too far off to represent means not in the foreseeable future.
+        Some(available) if growth_per_day > 0.0 => {
+            Duration::try_from_secs_f64(available as f64 / growth_per_day * SECS_PER_DAY).ok()
+        }
+        _ => None,
+    };
+    Some(Trend {
+        growth_per_day,
+        time_to_full,
+    })
+}
@@ -1,2 +1,7 @@
+pub mod diff;
 pub mod entities;
+pub mod history;
 pub mod ports;
+pub mod safety;
+pub mod tree;
+pub mod treemap;
@@ -1,28 +1,163 @@
-use crate::domain::entities::{FileStats, Recommendation};
+use crate::domain::entities::{
+    AuditEntry, AuditRecord, FileStats, Filesystem, PlannedRemoval, Recommendation, Removal,
+    ScanError, ScanProgress, SizeMode,
+};
 use anyhow::Result;
-use std::path::Path;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 
 pub trait Scanner: Send + Sync {
-    fn scan(&self, root: &Path, tx: Sender<ScanEvent>);
+    /// Starts scanning `root` in the background, streaming events to `tx`.
+    fn scan(&self, root: &Path, tx: Sender<ScanEvent>) -> ScanHandle;
+
+    /// Rescans `root` within an existing tree. Hard-linked (device, inode)
+    /// pairs in `counted` are already counted elsewhere in the tree, so
+    /// links to them below `root` are reported as duplicates.
+    fn rescan(
+        &self,
+        root: &Path,
+        counted: HashSet<(u64, u64)>,
+        tx: Sender<ScanEvent>,
+    ) -> ScanHandle {
+        let _ = counted;
+        self.scan(root, tx)
+    }
+}
+
+const RUNNING: u8 = 0;
+const PAUSED: u8 = 1;
+const CANCELLED: u8 = 2;
+
+/// Controls a running scan. Clones share the same scan.
+#[derive(Debug, Clone, Default)]
+pub struct ScanHandle {
+    state: Arc<AtomicU8>,
+}
+
+impl ScanHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.state.store(CANCELLED, Ordering::SeqCst);
+    }
+
+    pub fn pause(&self) {
+        let _ = self
+            .state
+            .compare_exchange(RUNNING, PAUSED, Ordering::SeqCst, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        let _ = self
+            .state
+            .compare_exchange(PAUSED, RUNNING, Ordering::SeqCst, Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state.load(Ordering::SeqCst) == PAUSED
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.load(Ordering::SeqCst) == CANCELLED
+    }
+
+    /// Called by scanners between entries: blocks while paused and returns
+    /// `false` once the scan has been cancelled.
+    pub fn checkpoint(&self) -> bool {
+        while self.is_paused() {
+            thread::sleep(Duration::from_millis(50));
+        }
+        !self.is_cancelled()
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum ScanEvent {
-    Progress {
-        total_size: u64,
-        files_scanned: usize,
-    },
+    Progress(ScanProgress),
     NewEntry(FileStats),
     Complete,
-    Error(String),
+    Error(ScanError),
+}
+
+/// What a dry run would have removed, shared between the dry-run adapters
+/// and whoever reports on them. Clones share the same log.
+#[derive(Debug, Clone, Default)]
+pub struct DryRunLog {
+    entries: Arc<Mutex<Vec<PlannedRemoval>>>,
+}
+
+impl DryRunLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, removal: PlannedRemoval) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.push(removal);
+        }
+    }
+
+    /// Recorded removals, in the order they happened.
+    pub fn entries(&self) -> Vec<PlannedRemoval> {
+        self.entries
+            .lock()
+            .map(|entries| entries.clone())
+            .unwrap_or_default()
+    }
+
+    /// What all recorded removals would have freed, in `mode`.
+    pub fn total(&self, mode: SizeMode) -> u64 {
+        self.entries()
+            .iter()
+            .map(|removal| removal.size(mode))
+            .sum()
+    }
 }
 
 pub trait Cleaner: Send + Sync {
     fn delete_item(&self, path: &Path) -> Result<u64>;
+
+    fn removal(&self) -> Removal {
+        Removal::Permanent
+    }
+
+    /// Groups the deletions that follow into one operation, such as a batch
+    /// or a cleanup, so they are undone together.
+    fn begin_operation(&self) {}
+
+    /// Puts back the `count` most recent operations, newest first, and
+    /// returns the restored paths.
+    fn undo(&self, _count: usize) -> Result<Vec<PathBuf>> {
+        anyhow::bail!("Deleted items cannot be restored")
+    }
 }
 
 pub trait Analyzer: Send + Sync {
     fn analyze(&self) -> Result<Option<Recommendation>>;
     fn prune(&self) -> Result<()>; // Should probably be generalized or handled via command pattern
+
+    /// What `prune` does to what it removes.
+    fn removal(&self) -> Removal {
+        Removal::Permanent
+    }
+}
+
+pub trait AuditLog: Send + Sync {
+    /// Appends `entry`, stamped with the current time and user.
+    fn append(&self, entry: AuditEntry) -> Result<()>;
+
+    /// Every record, oldest first.
+    fn records(&self) -> Result<Vec<AuditRecord>>;
+}
+
+pub trait MountLister: Send + Sync {
+    /// Mounted filesystems, ordered by mount point.
+    fn filesystems(&self) -> Vec<Filesystem>;
 }
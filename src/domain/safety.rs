@@ -0,0 +1,157 @@
+//! Rules that keep deletions away from what must never be deleted, and
+//! limits above which an operation needs a second confirmation.
+
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+/// System directories that may be cleaned out but never removed themselves.
+pub const SYSTEM_DIRS: &[&str] = &[
+    "/",
+    "/bin",
+    "/boot",
+    "/dev",
+    "/etc",
+    "/home",
+    "/lib",
+    "/lib32",
+    "/lib64",
+    "/opt",
+    "/proc",
+    "/root",
+    "/run",
+    "/sbin",
+    "/srv",
+    "/sys",
+    "/tmp",
+    "/usr",
+    "/usr/bin",
+    "/usr/lib",
+    "/usr/local",
+    "/usr/sbin",
+    "/usr/share",
+    "/var",
+    "/var/cache",
+    "/var/lib",
+    "/var/log",
+    "/Applications",
+    "/Library",
+    "/System",
+    "/Users",
+];
+
+const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024 * 1024;
+const DEFAULT_MAX_ITEMS: usize = 1_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SafetyRules {
+    /// Directories that must survive, along with everything above them;
+    /// what is inside them may go. System directories and the home
+    /// directory.
+    pub keep: Vec<PathBuf>,
+    /// Paths that must survive with everything inside and above them.
+    pub protected: Vec<PathBuf>,
+    /// Entry names protected like `protected` wherever they appear.
+    pub protected_names: Vec<String>,
+    /// Bytes one operation may remove without a second confirmation.
+    pub max_bytes: u64,
+    /// Items one operation may remove without a second confirmation.
+    pub max_items: usize,
+}
+
+impl Default for SafetyRules {
+    fn default() -> Self {
+        Self {
+            keep: SYSTEM_DIRS.iter().map(PathBuf::from).collect(),
+            protected: Vec::new(),
+            protected_names: vec![".git".to_string()],
+            max_bytes: DEFAULT_MAX_BYTES,
+            max_items: DEFAULT_MAX_ITEMS,
+        }
+    }
+}
+
+/// Why a deletion was refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Blocked {
+    ScanRoot,
+    OutsideRoot(PathBuf),
+    /// It is, or contains, a directory that must be kept.
+    Keeps(PathBuf),
+    /// It is, lies inside, or contains a protected path.
+    Protected(PathBuf),
+}
+
+impl fmt::Display for Blocked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Blocked::ScanRoot => write!(f, "refusing to delete the scan root"),
+            Blocked::OutsideRoot(root) => {
+                write!(
+                    f,
+                    "refusing to delete outside the scan root {}",
+                    root.display()
+                )
+            }
+            Blocked::Keeps(dir) => write!(
+                f,
+                "refusing to delete {}, a system or home directory",
+                dir.display()
+            ),
+            Blocked::Protected(path) => {
+                write!(f, "refusing to delete protected path {}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Blocked {}
+
+impl SafetyRules {
+    /// Checks that deleting `path` from a scan of `root` removes nothing
+    /// protected. Protected names below `path` are not visible from the path
+    /// alone; callers check those with [`is_protected_name`](Self::is_protected_name).
+    pub fn check(&self, root: &Path, path: &Path) -> Result<(), Blocked> {
+        if path
+            .components()
+            .any(|component| component == Component::ParentDir)
+            || !path.starts_with(root)
+        {
+            return Err(Blocked::OutsideRoot(root.to_path_buf()));
+        }
+        if path == root {
+            return Err(Blocked::ScanRoot);
+        }
+        if let Some(dir) = self.keep.iter().find(|dir| dir.starts_with(path)) {
+            return Err(Blocked::Keeps(dir.clone()));
+        }
+        if let Some(protected) = self
+            .protected
+            .iter()
+            .find(|protected| protected.starts_with(path) || path.starts_with(protected))
+        {
+            return Err(Blocked::Protected(protected.clone()));
+        }
+        // Inside a protected name, e.g. `repo/.git/objects`.
+        let mut prefix = PathBuf::new();
+        for component in path.components() {
+            prefix.push(component);
+            if let Component::Normal(name) = component {
+                if self.is_protected_name(&name.to_string_lossy()) {
+                    return Err(Blocked::Protected(prefix));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_protected_name(&self, name: &str) -> bool {
+        self.protected_names
+            .iter()
+            .any(|protected| protected == name)
+    }
+
+    /// Whether removing `bytes` in `items` needs a second confirmation.
+    pub fn exceeds_limits(&self, bytes: u64, items: usize) -> bool {
+        bytes > self.max_bytes || items > self.max_items
+    }
+}
@@ -0,0 +1,431 @@
+use crate::domain::entities::{FileStats, FileType, SizeMode, SortKey, SortOrder, SortSpec};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+/// Index of a node in the [`DirTree`] arena.
+pub type NodeId = usize;
+
+type NameId = u32;
+
+/// Stores each distinct path segment once; nodes refer to names by id.
+#[derive(Debug, Default)]
+struct NameInterner {
+    names: Vec<OsString>,
+    lookup: HashMap<OsString, NameId>,
+}
+
+impl NameInterner {
+    fn intern(&mut self, name: &OsStr) -> NameId {
+        if let Some(&id) = self.lookup.get(name) {
+            return id;
+        }
+        let id = self.names.len() as NameId;
+        self.names.push(name.to_os_string());
+        self.lookup.insert(name.to_os_string(), id);
+        id
+    }
+
+    fn get(&self, name: &OsStr) -> Option<NameId> {
+        self.lookup.get(name).copied()
+    }
+
+    fn resolve(&self, id: NameId) -> &OsStr {
+        &self.names[id as usize]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    name: NameId,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    alive: bool,
+    /// Sizes of the entry itself, excluding anything below it.
+    own_apparent: u64,
+    own_allocated: u64,
+    /// Cumulative apparent size of the entry and its whole subtree.
+    pub apparent_size: u64,
+    /// Cumulative allocated size of the entry and its whole subtree.
+    pub allocated_size: u64,
+    /// Number of entries below this node.
+    pub descendants: usize,
+    pub is_dir: bool,
+    pub modified: SystemTime,
+    pub file_type: FileType,
+    pub links: u64,
+    pub duplicate_inode: bool,
+    pub is_mount_point: bool,
+    pub inode: Option<(u64, u64)>,
+    /// Set when an error below this node left its size incomplete.
+    pub incomplete: bool,
+}
+
+impl Node {
+    pub fn size(&self, mode: SizeMode) -> u64 {
+        match mode {
+            SizeMode::Disk => self.allocated_size,
+            SizeMode::Apparent => self.apparent_size,
+            SizeMode::Count => 1 + self.descendants as u64,
+        }
+    }
+
+    /// Size of the entry itself, excluding anything below it.
+    pub fn own_size(&self, mode: SizeMode) -> u64 {
+        match mode {
+            SizeMode::Disk => self.own_allocated,
+            SizeMode::Apparent => self.own_apparent,
+            SizeMode::Count => 1,
+        }
+    }
+
+    /// Size this node contributes to its ancestors. Inodes already counted
+    /// under another hard link contribute nothing.
+    pub fn counted_size(&self, mode: SizeMode) -> u64 {
+        if self.duplicate_inode {
+            0
+        } else {
+            self.size(mode)
+        }
+    }
+
+    fn new(name: NameId, parent: Option<NodeId>, is_dir: bool) -> Self {
+        Node {
+            name,
+            parent,
+            children: Vec::new(),
+            alive: true,
+            own_apparent: 0,
+            own_allocated: 0,
+            apparent_size: 0,
+            allocated_size: 0,
+            descendants: 0,
+            is_dir,
+            modified: SystemTime::UNIX_EPOCH,
+            file_type: FileType::Normal,
+            links: 1,
+            duplicate_inode: false,
+            is_mount_point: false,
+            inode: None,
+            incomplete: false,
+        }
+    }
+}
+
+/// In-memory directory tree of a scan, rooted at the scanned path.
+///
+/// Nodes live in an arena and reference their parent and children by index,
+/// so navigating a directory costs O(children) regardless of the tree size.
+/// Directory sizes and descendant counts are kept rolled up on every change.
+#[derive(Debug)]
+pub struct DirTree {
+    root_path: PathBuf,
+    nodes: Vec<Node>,
+    names: NameInterner,
+    child_lookup: HashMap<(NodeId, NameId), NodeId>,
+    free: Vec<NodeId>,
+}
+
+impl DirTree {
+    pub fn new(root_path: impl Into<PathBuf>) -> Self {
+        let mut names = NameInterner::default();
+        let root_name = names.intern(OsStr::new(""));
+        DirTree {
+            root_path: root_path.into(),
+            nodes: vec![Node::new(root_name, None, true)],
+            names,
+            child_lookup: HashMap::new(),
+            free: Vec::new(),
+        }
+    }
+
+    pub fn root(&self) -> NodeId {
+        0
+    }
+
+    pub fn root_path(&self) -> &Path {
+        &self.root_path
+    }
+
+    /// Number of entries in the tree, not counting the root.
+    pub fn len(&self) -> usize {
+        self.nodes[self.root()].descendants
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id].parent
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.nodes[id].children
+    }
+
+    pub fn name(&self, id: NodeId) -> &OsStr {
+        self.names.resolve(self.nodes[id].name)
+    }
+
+    /// Rebuilds the full path of a node from its name segments.
+    pub fn path(&self, id: NodeId) -> PathBuf {
+        let mut segments = Vec::new();
+        let mut current = id;
+        while let Some(parent) = self.nodes[current].parent {
+            segments.push(self.name(current));
+            current = parent;
+        }
+        let mut path = self.root_path.clone();
+        path.extend(segments.iter().rev());
+        path
+    }
+
+    /// Returns a `FileStats` view of a node, with cumulative size and count.
+    pub fn stats(&self, id: NodeId) -> FileStats {
+        let node = &self.nodes[id];
+        FileStats {
+            path: self.path(id),
+            size: node.apparent_size,
+            allocated_size: node.allocated_size,
+            is_dir: node.is_dir,
+            modified: node.modified,
+            children_count: node.is_dir.then_some(node.descendants),
+            file_type: node.file_type.clone(),
+            links: node.links,
+            duplicate_inode: node.duplicate_inode,
+            is_mount_point: node.is_mount_point,
+            inode: node.inode,
+        }
+    }
+
+    /// Sorts `ids` by `spec`, measuring sizes in `mode`. Ties go by name.
+    pub fn sort(&self, ids: &mut [NodeId], spec: SortSpec, mode: SizeMode) {
+        ids.sort_by(|&a, &b| self.compare(a, b, spec, mode));
+    }
+
+    fn compare(&self, a: NodeId, b: NodeId, spec: SortSpec, mode: SizeMode) -> Ordering {
+        let (node_a, node_b) = (&self.nodes[a], &self.nodes[b]);
+        if spec.dirs_first && node_a.is_dir != node_b.is_dir {
+            return node_b.is_dir.cmp(&node_a.is_dir);
+        }
+        let by_name = || self.compare_names(a, b);
+        let ordering = match spec.key {
+            SortKey::Size => node_a.size(mode).cmp(&node_b.size(mode)),
+            SortKey::Name => by_name(),
+            SortKey::Modified => node_a.modified.cmp(&node_b.modified),
+            SortKey::Count => node_a.descendants.cmp(&node_b.descendants),
+            SortKey::Type => node_a.file_type.key().cmp(node_b.file_type.key()),
+            SortKey::Extension => self.extension(a).cmp(&self.extension(b)),
+        };
+        let ordering = match spec.order {
+            SortOrder::Asc => ordering,
+            SortOrder::Desc => ordering.reverse(),
+        };
+        ordering.then_with(|| {
+            if spec.key == SortKey::Name {
+                Ordering::Equal
+            } else {
+                by_name()
+            }
+        })
+    }
+
+    /// Case-insensitive, falling back to the exact name to stay total.
+    fn compare_names(&self, a: NodeId, b: NodeId) -> Ordering {
+        let (a, b) = (
+            self.name(a).to_string_lossy(),
+            self.name(b).to_string_lossy(),
+        );
+        a.to_lowercase()
+            .cmp(&b.to_lowercase())
+            .then_with(|| a.cmp(&b))
+    }
+
+    /// Lowercased extension of a file; directories have none.
+    fn extension(&self, id: NodeId) -> String {
+        if self.nodes[id].is_dir {
+            return String::new();
+        }
+        Path::new(self.name(id))
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default()
+    }
+
+    /// Iterates over every live node below the root.
+    pub fn iter(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, node)| node.alive)
+            .map(|(id, _)| id)
+    }
+
+    pub fn find(&self, path: &Path) -> Option<NodeId> {
+        let relative = path.strip_prefix(&self.root_path).ok()?;
+        let mut current = self.root();
+        for component in relative.components() {
+            let Component::Normal(segment) = component else {
+                continue;
+            };
+            let name = self.names.get(segment)?;
+            current = *self.child_lookup.get(&(current, name))?;
+        }
+        Some(current)
+    }
+
+    /// Inserts or updates the entry described by `stats`, creating any missing
+    /// intermediate directories. Returns `None` if the path is outside the root.
+    pub fn insert(&mut self, stats: FileStats) -> Option<NodeId> {
+        let relative = stats.path.strip_prefix(&self.root_path).ok()?;
+        let segments: Vec<OsString> = relative
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(segment) => Some(segment.to_os_string()),
+                _ => None,
+            })
+            .collect();
+
+        let mut current = self.root();
+        for segment in &segments {
+            current = self.child_or_insert(current, segment);
+        }
+
+        let node = &mut self.nodes[current];
+        let counted_before = (
+            node.counted_size(SizeMode::Apparent),
+            node.counted_size(SizeMode::Disk),
+        );
+        node.apparent_size = node.apparent_size - node.own_apparent + stats.size;
+        node.allocated_size = node.allocated_size - node.own_allocated + stats.allocated_size;
+        node.own_apparent = stats.size;
+        node.own_allocated = stats.allocated_size;
+        node.is_dir = stats.is_dir;
+        node.modified = stats.modified;
+        node.file_type = stats.file_type;
+        node.links = stats.links;
+        node.duplicate_inode = stats.duplicate_inode;
+        node.is_mount_point = stats.is_mount_point;
+        node.inode = stats.inode;
+        let apparent_delta =
+            node.counted_size(SizeMode::Apparent) as i128 - counted_before.0 as i128;
+        let allocated_delta = node.counted_size(SizeMode::Disk) as i128 - counted_before.1 as i128;
+        if let Some(parent) = node.parent {
+            self.apply_size_delta(parent, apparent_delta, allocated_delta);
+        }
+
+        Some(current)
+    }
+
+    /// Removes a node and its whole subtree, returning its stats as they were
+    /// before removal. The root itself cannot be removed.
+    pub fn remove(&mut self, id: NodeId) -> Option<FileStats> {
+        let parent = self.nodes[id].parent?;
+        let removed = self.stats(id);
+        let counted_apparent = self.nodes[id].counted_size(SizeMode::Apparent);
+        let counted_allocated = self.nodes[id].counted_size(SizeMode::Disk);
+        let count = 1 + self.nodes[id].descendants;
+
+        self.nodes[parent].children.retain(|&child| child != id);
+        self.child_lookup.remove(&(parent, self.nodes[id].name));
+
+        let mut ancestor = Some(parent);
+        while let Some(current) = ancestor {
+            let node = &mut self.nodes[current];
+            node.apparent_size = node.apparent_size.saturating_sub(counted_apparent);
+            node.allocated_size = node.allocated_size.saturating_sub(counted_allocated);
+            node.descendants = node.descendants.saturating_sub(count);
+            ancestor = node.parent;
+        }
+
+        let mut stack = vec![id];
+        while let Some(current) = stack.pop() {
+            let children = std::mem::take(&mut self.nodes[current].children);
+            for &child in &children {
+                self.child_lookup.remove(&(current, self.nodes[child].name));
+            }
+            stack.extend(children);
+            self.nodes[current].alive = false;
+            self.free.push(current);
+        }
+
+        Some(removed)
+    }
+
+    /// Flags the entry at `path` and all its ancestors as having an
+    /// incomplete size. Unknown paths flag their nearest known ancestor.
+    pub fn mark_incomplete(&mut self, path: &Path) {
+        let mut target = path;
+        let mut id = self.find(target);
+        while id.is_none() {
+            match target.parent() {
+                Some(parent) => {
+                    target = parent;
+                    id = self.find(target);
+                }
+                None => return,
+            }
+        }
+
+        let mut current = id;
+        while let Some(index) = current {
+            self.nodes[index].incomplete = true;
+            current = self.nodes[index].parent;
+        }
+    }
+
+    /// Clears the incomplete flag on `id` and all its ancestors.
+    pub fn clear_incomplete(&mut self, id: NodeId) {
+        let mut current = Some(id);
+        while let Some(index) = current {
+            self.nodes[index].incomplete = false;
+            current = self.nodes[index].parent;
+        }
+    }
+
+    fn child_or_insert(&mut self, parent: NodeId, segment: &OsStr) -> NodeId {
+        let name = self.names.intern(segment);
+        if let Some(&child) = self.child_lookup.get(&(parent, name)) {
+            return child;
+        }
+
+        let node = Node::new(name, Some(parent), true);
+        let child = match self.free.pop() {
+            Some(slot) => {
+                self.nodes[slot] = node;
+                slot
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+        self.nodes[parent].children.push(child);
+        self.child_lookup.insert((parent, name), child);
+
+        let mut ancestor = Some(parent);
+        while let Some(current) = ancestor {
+            self.nodes[current].descendants += 1;
+            ancestor = self.nodes[current].parent;
+        }
+        child
+    }
+
+    fn apply_size_delta(&mut self, id: NodeId, apparent: i128, allocated: i128) {
+        let mut current = Some(id);
+        while let Some(index) = current {
+            let node = &mut self.nodes[index];
+            node.apparent_size = (node.apparent_size as i128 + apparent).max(0) as u64;
+            node.allocated_size = (node.allocated_size as i128 + allocated).max(0) as u64;
+            current = node.parent;
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! Squarified treemap layout (Bruls, Huizing and van Wijk) and spatial
+//! navigation between its tiles.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn right(&self) -> f64 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> f64 {
+        self.y + self.height
+    }
+
+    pub fn area(&self) -> f64 {
+        self.width * self.height
+    }
+
+    fn center(&self) -> (f64, f64) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Lays out `sizes`, sorted largest first, as tiles filling `area` with
+/// areas proportional to the sizes. Tiles come back in the same order;
+/// zero sizes get empty tiles.
+pub fn squarify(sizes: &[u64], area: Rect) -> Vec<Rect> {
+    let total: u64 = sizes.iter().sum();
+    let empty = Rect::new(area.x, area.y, 0.0, 0.0);
+    if total == 0 || area.area() <= 0.0 {
+        return vec![empty; sizes.len()];
+    }
+    let scale = area.area() / total as f64;
+    let areas: Vec<f64> = sizes.iter().map(|&size| size as f64 * scale).collect();
+
+    let mut tiles = Vec::with_capacity(sizes.len());
+    let mut remaining = area;
+    let mut start = 0;
+    while start < areas.len() {
+        if areas[start] <= 0.0 {
+            tiles.push(empty);
+            start += 1;
+            continue;
+        }
+        // Grow the row while that keeps its tiles closer to squares.
+        let side = remaining.width.min(remaining.height);
+        let mut end = start + 1;
+        while end < areas.len()
+            && areas[end] > 0.0
+            && worst(&areas[start..=end], side) <= worst(&areas[start..end], side)
+        {
+            end += 1;
+        }
+        remaining = lay_out_row(&areas[start..end], remaining, &mut tiles);
+        start = end;
+    }
+    tiles
+}
+
+/// Worst aspect ratio of a row of tile areas laid along a side of `side`.
+fn worst(row: &[f64], side: f64) -> f64 {
+    let sum: f64 = row.iter().sum();
+    let (min, max) = row.iter().fold((f64::MAX, 0.0_f64), |(min, max), &a| {
+        (min.min(a), max.max(a))
+    });
+    let side2 = side * side;
+    let sum2 = sum * sum;
+    (side2 * max / sum2).max(sum2 / (side2 * min))
+}
+
+/// Places a row along the shorter side of `area` and returns what is left.
+fn lay_out_row(row: &[f64], area: Rect, tiles: &mut Vec<Rect>) -> Rect {
+    let sum: f64 = row.iter().sum();
+    if area.width >= area.height {
+        // A column on the left.
+        let width = sum / area.height;
+        let mut y = area.y;
+        for &a in row {
+            let height = a / width;
+            tiles.push(Rect::new(area.x, y, width, height));
+            y += height;
+        }
+        Rect::new(area.x + width, area.y, area.width - width, area.height)
+    } else {
+        // A row along the top.
+        let height = sum / area.width;
+        let mut x = area.x;
+        for &a in row {
+            let width = a / height;
+            tiles.push(Rect::new(x, area.y, width, height));
+            x += width;
+        }
+        Rect::new(area.x, area.y + height, area.width, area.height - height)
+    }
+}
+
+/// The tile next to `tiles[from]` in `direction`: the nearest one sharing
+/// an edge span if any, otherwise the nearest one beyond it.
+pub fn neighbour(tiles: &[Rect], from: usize, direction: Direction) -> Option<usize> {
+    const EPSILON: f64 = 1e-6;
+    let current = tiles.get(from)?;
+    let (cx, cy) = current.center();
+
+    tiles
+        .iter()
+        .enumerate()
+        .filter(|&(i, tile)| i != from && tile.area() > 0.0)
+        .filter_map(|(i, tile)| {
+            let (gap, overlaps, offset) = match direction {
+                Direction::Right => (
+                    tile.x - current.right(),
+                    tile.y < current.bottom() && tile.bottom() > current.y,
+                    (tile.center().1 - cy).abs(),
+                ),
+                Direction::Left => (
+                    current.x - tile.right(),
+                    tile.y < current.bottom() && tile.bottom() > current.y,
+                    (tile.center().1 - cy).abs(),
+                ),
+                Direction::Down => (
+                    tile.y - current.bottom(),
+                    tile.x < current.right() && tile.right() > current.x,
+                    (tile.center().0 - cx).abs(),
+                ),
+                Direction::Up => (
+                    current.y - tile.bottom(),
+                    tile.x < current.right() && tile.right() > current.x,
+                    (tile.center().0 - cx).abs(),
+                ),
+            };
+            (gap > -EPSILON).then_some((i, !overlaps, gap, offset))
+        })
+        .min_by(|a, b| {
+            (a.1, a.2, a.3)
+                .partial_cmp(&(b.1, b.2, b.3))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+        .map(|(i, ..)| i)
+}
@@ -0,0 +1,187 @@
+//! Append-only audit log of deletions and prunes, one JSON record per line.
+
+use crate::domain::entities::{AuditEntry, AuditRecord, FileType, RecommendationCategory, Removal};
+use crate::domain::ports::AuditLog;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub struct AuditStore {
+    path: PathBuf,
+    user: String,
+}
+
+impl AuditStore {
+    /// Log at `$XDG_DATA_HOME/diskdoc/audit.jsonl`, falling back to
+    /// `~/.local/share/diskdoc/audit.jsonl`.
+    pub fn new() -> Result<Self> {
+        let data = env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| {
+                env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+            })
+            .context("Cannot locate the data directory: neither XDG_DATA_HOME nor HOME is set")?;
+        Ok(Self::with_path(data.join("diskdoc").join("audit.jsonl")))
+    }
+
+    /// Log at `path`, e.g. one shared by every user of a machine.
+    pub fn with_path(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            user: current_user(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AuditLog for AuditStore {
+    fn append(&self, entry: AuditEntry) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open audit log: {}", self.path.display()))?;
+        let record = AuditRecord {
+            time: SystemTime::now(),
+            user: self.user.clone(),
+            entry,
+        };
+        // One write per line, so concurrent writers do not interleave.
+        let mut line = serde_json::to_string(&Line::from(&record))?;
+        line.push('\n');
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("Failed to write audit log: {}", self.path.display()))
+    }
+
+    /// Unreadable lines are skipped.
+    fn records(&self) -> Result<Vec<AuditRecord>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to open audit log: {}", self.path.display()))
+            }
+        };
+        Ok(BufReader::new(file)
+            .lines()
+            .map_while(|line| line.ok())
+            .filter_map(|line| serde_json::from_str::<Line>(&line).ok())
+            .filter_map(|line| line.into_record())
+            .collect())
+    }
+}
+
+/// The name of the real user, falling back to `$USER` and then to the
+/// numeric user id.
+fn current_user() -> String {
+    account_name()
+        .or_else(|| env::var("USER").ok())
+        .filter(|user| !user.is_empty())
+        .unwrap_or_else(|| super::trash::user_id().to_string())
+}
+
+#[cfg(unix)]
+fn account_name() -> Option<String> {
+    let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut buf = vec![0 as libc::c_char; 4096];
+    let mut result = std::ptr::null_mut();
+    // SAFETY: every pointer refers to live, correctly sized storage, and
+    // `pw_name` points into `buf` when an entry is found.
+    let rc = unsafe {
+        libc::getpwuid_r(
+            libc::getuid(),
+            &mut passwd,
+            buf.as_mut_ptr(),
+            buf.len(),
+            &mut result,
+        )
+    };
+    if rc != 0 || result.is_null() {
+        return None;
+    }
+    let name = unsafe { std::ffi::CStr::from_ptr(passwd.pw_name) };
+    Some(name.to_string_lossy().into_owned())
+}
+
+#[cfg(not(unix))]
+fn account_name() -> Option<String> {
+    None
+}
+
+/// On-disk form of an [`AuditRecord`].
+#[derive(Serialize, Deserialize)]
+struct Line {
+    time: u64,
+    user: String,
+    target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recommendation: Option<String>,
+    removal: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    freed: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl From<&AuditRecord> for Line {
+    fn from(record: &AuditRecord) -> Self {
+        let entry = &record.entry;
+        Line {
+            time: record
+                .time
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            user: record.user.clone(),
+            target: entry.target.clone(),
+            size: entry.size,
+            file_type: entry.file_type.as_ref().map(|t| t.key().to_string()),
+            recommendation: entry.recommendation.as_ref().map(|r| r.key().to_string()),
+            removal: entry.removal.key().to_string(),
+            freed: entry.outcome.as_ref().ok().copied(),
+            error: entry.outcome.as_ref().err().cloned(),
+        }
+    }
+}
+
+impl Line {
+    fn into_record(self) -> Option<AuditRecord> {
+        let outcome = match (self.freed, self.error) {
+            (_, Some(error)) => Err(error),
+            (Some(freed), None) => Ok(freed),
+            (None, None) => return None,
+        };
+        Some(AuditRecord {
+            time: UNIX_EPOCH + Duration::from_secs(self.time),
+            user: self.user,
+            entry: AuditEntry {
+                target: self.target,
+                size: self.size,
+                file_type: self.file_type.as_deref().and_then(FileType::from_key),
+                recommendation: self
+                    .recommendation
+                    .as_deref()
+                    .and_then(RecommendationCategory::from_key),
+                removal: Removal::from_key(&self.removal)?,
+                outcome,
+            },
+        })
+    }
+}
@@ -0,0 +1,89 @@
+//! Stand-ins for the destructive adapters that record what they would have
+//! removed instead of removing it, for `--dry-run`.
+
+use super::scanner::walker::allocated_size;
+use crate::domain::entities::{PlannedRemoval, Recommendation, Removal};
+use crate::domain::ports::{Analyzer, Cleaner, DryRunLog};
+use anyhow::{Context, Result};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub struct DryRunCleaner {
+    log: DryRunLog,
+}
+
+impl DryRunCleaner {
+    pub fn new(log: DryRunLog) -> Self {
+        Self { log }
+    }
+}
+
+impl Cleaner for DryRunCleaner {
+    fn delete_item(&self, path: &Path) -> Result<u64> {
+        let mut removal = PlannedRemoval {
+            target: path.display().to_string(),
+            allocated_size: 0,
+            apparent_size: 0,
+            entries: 0,
+        };
+        measure(path, &mut removal).context("Failed to read metadata")?;
+        let size = removal.apparent_size;
+        self.log.record(removal);
+        Ok(size)
+    }
+
+    fn removal(&self) -> Removal {
+        Removal::DryRun
+    }
+}
+
+/// Adds `path` and everything below it to `removal`, in every size mode.
+fn measure(path: &Path, removal: &mut PlannedRemoval) -> io::Result<()> {
+    let meta = fs::symlink_metadata(path)?;
+    removal.allocated_size += allocated_size(&meta);
+    removal.apparent_size += meta.len();
+    removal.entries += 1;
+    if meta.is_dir() {
+        for entry in fs::read_dir(path)? {
+            let _ = measure(&entry?.path(), removal);
+        }
+    }
+    Ok(())
+}
+
+/// Analyzes through the wrapped analyzer but only records its prunes.
+pub struct DryRunAnalyzer {
+    inner: Box<dyn Analyzer>,
+    log: DryRunLog,
+}
+
+impl DryRunAnalyzer {
+    pub fn new(inner: Box<dyn Analyzer>, log: DryRunLog) -> Self {
+        Self { inner, log }
+    }
+}
+
+impl Analyzer for DryRunAnalyzer {
+    fn analyze(&self) -> Result<Option<Recommendation>> {
+        self.inner.analyze()
+    }
+
+    fn prune(&self) -> Result<()> {
+        // What a prune frees is what the analysis reports as reclaimable;
+        // it removes no entries of the tree.
+        if let Some(rec) = self.inner.analyze()? {
+            self.log.record(PlannedRemoval {
+                target: rec.action_command.unwrap_or(rec.description),
+                allocated_size: rec.size,
+                apparent_size: rec.size,
+                entries: 0,
+            });
+        }
+        Ok(())
+    }
+
+    fn removal(&self) -> Removal {
+        Removal::DryRun
+    }
+}
@@ -0,0 +1,128 @@
+//! Append-only scan history, one JSON record per line.
+
+use crate::domain::history::{FilesystemSpace, HistoryRecord};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+pub struct HistoryStore {
+    path: PathBuf,
+}
+
+impl HistoryStore {
+    /// Store at `$XDG_DATA_HOME/diskdoc/history.jsonl`, falling back to
+    /// `~/.local/share/diskdoc/history.jsonl`.
+    pub fn new() -> Result<Self> {
+        let data = env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| {
+                env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+            })
+            .context("Cannot locate the data directory: neither XDG_DATA_HOME nor HOME is set")?;
+        Ok(Self::with_path(data.join("diskdoc").join("history.jsonl")))
+    }
+
+    pub fn with_path(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&self, record: &HistoryRecord) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open history: {}", self.path.display()))?;
+        let mut line = serde_json::to_string(&Line::from(record))?;
+        line.push('\n');
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("Failed to write history: {}", self.path.display()))?;
+        Ok(())
+    }
+
+    /// Records of scans of `root`, oldest first. Unreadable lines are skipped.
+    pub fn records_for(&self, root: &Path) -> Result<Vec<HistoryRecord>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to open history: {}", self.path.display()))
+            }
+        };
+        let root = root.to_string_lossy();
+        let mut records: Vec<HistoryRecord> = BufReader::new(file)
+            .lines()
+            .map_while(|line| line.ok())
+            .filter_map(|line| serde_json::from_str::<Line>(&line).ok())
+            .filter(|line| line.root == root)
+            .map(HistoryRecord::from)
+            .collect();
+        records.sort_by_key(|record| record.time);
+        Ok(records)
+    }
+}
+
+/// On-disk form of a [`HistoryRecord`].
+#[derive(Serialize, Deserialize)]
+struct Line {
+    root: String,
+    time: u64,
+    size: u64,
+    entries: usize,
+    top_level: BTreeMap<String, u64>,
+    file_types: BTreeMap<String, u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fs_total: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fs_available: Option<u64>,
+}
+
+impl From<&HistoryRecord> for Line {
+    fn from(record: &HistoryRecord) -> Self {
+        Line {
+            root: record.root.to_string_lossy().into_owned(),
+            time: record
+                .time
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            size: record.size,
+            entries: record.entries,
+            top_level: record.top_level.clone(),
+            file_types: record.file_types.clone(),
+            fs_total: record.filesystem.map(|fs| fs.total),
+            fs_available: record.filesystem.map(|fs| fs.available),
+        }
+    }
+}
+
+impl From<Line> for HistoryRecord {
+    fn from(line: Line) -> Self {
+        HistoryRecord {
+            root: PathBuf::from(line.root),
+            time: UNIX_EPOCH + Duration::from_secs(line.time),
+            size: line.size,
+            entries: line.entries,
+            top_level: line.top_level,
+            file_types: line.file_types,
+            filesystem: match (line.fs_total, line.fs_available) {
+                (Some(total), Some(available)) => Some(FilesystemSpace { total, available }),
+                _ => None,
+            },
+        }
+    }
+}
@@ -1,3 +1,11 @@
+pub mod audit;
 pub mod cleaner;
 pub mod docker;
+pub mod dry_run;
+pub mod history;
+pub mod mounts;
+pub mod ncdu;
+pub mod quarantine;
 pub mod scanner; // Will need to move scanner logic here
+pub mod snapshot;
+pub mod trash;
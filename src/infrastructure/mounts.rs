@@ -0,0 +1,140 @@
+//! Mounted filesystems, from sysinfo plus the kernel mount table.
+
+use crate::domain::entities::Filesystem;
+use crate::domain::ports::MountLister;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use sysinfo::Disks;
+
+pub struct SysinfoMounts;
+
+impl SysinfoMounts {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for SysinfoMounts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MountLister for SysinfoMounts {
+    fn filesystems(&self) -> Vec<Filesystem> {
+        let table: HashMap<PathBuf, MountEntry> = fs::read_to_string("/proc/self/mounts")
+            .map(|text| parse_mounts(&text))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|entry| (entry.mount_point.clone(), entry))
+            .collect();
+
+        let disks = Disks::new_with_refreshed_list();
+        let mut filesystems: Vec<Filesystem> = disks
+            .list()
+            .iter()
+            .map(|disk| {
+                let mount_point = disk.mount_point().to_path_buf();
+                let entry = table.get(&mount_point);
+                let (inodes_total, inodes_free) = match inode_counts(&mount_point) {
+                    Some((total, free)) => (Some(total), Some(free)),
+                    None => (None, None),
+                };
+                Filesystem {
+                    device: entry
+                        .map(|e| e.device.clone())
+                        .unwrap_or_else(|| disk.name().to_string_lossy().into_owned()),
+                    fs_type: disk.file_system().to_string_lossy().into_owned(),
+                    options: entry.map(|e| e.options.clone()).unwrap_or_default(),
+                    mount_point,
+                    total: disk.total_space(),
+                    available: disk.available_space(),
+                    inodes_total,
+                    inodes_free,
+                }
+            })
+            .collect();
+        filesystems.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+        filesystems.dedup_by(|a, b| a.mount_point == b.mount_point);
+        filesystems
+    }
+}
+
+/// One line of `/proc/self/mounts`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MountEntry {
+    pub device: String,
+    pub mount_point: PathBuf,
+    pub fs_type: String,
+    pub options: Vec<String>,
+}
+
+/// Parses a mount table in `/proc/self/mounts` format. Later entries for
+/// the same mount point shadow earlier ones, as they do in the kernel.
+pub fn parse_mounts(text: &str) -> Vec<MountEntry> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let device = unescape(fields.next()?);
+            let mount_point = PathBuf::from(unescape(fields.next()?));
+            let fs_type = fields.next()?.to_string();
+            let options = fields
+                .next()?
+                .split(',')
+                .map(|option| option.to_string())
+                .collect();
+            Some(MountEntry {
+                device,
+                mount_point,
+                fs_type,
+                options,
+            })
+        })
+        .collect()
+}
+
+/// Decodes the octal escapes (`\040` for a space) used in the mount table.
+fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let octal = bytes.get(i + 1..i + 4).and_then(|digits| {
+            std::str::from_utf8(digits)
+                .ok()
+                .and_then(|digits| u8::from_str_radix(digits, 8).ok())
+        });
+        match octal {
+            Some(byte) if bytes[i] == b'\\' => {
+                out.push(byte);
+                i += 4;
+            }
+            _ => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Total and free inodes of the filesystem mounted at `path`.
+#[cfg(unix)]
+fn inode_counts(path: &Path) -> Option<(u64, u64)> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `path` is NUL-terminated and `stat` is a valid out pointer.
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some((stat.f_files as u64, stat.f_ffree as u64))
+}
+
+#[cfg(not(unix))]
+fn inode_counts(_path: &Path) -> Option<(u64, u64)> {
+    None
+}
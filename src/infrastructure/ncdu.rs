@@ -0,0 +1,324 @@
+//! Reading and writing ncdu JSON exports (`ncdu -o` / `ncdu -f`).
+//!
+//! The format is `[1, 2, {metadata}, root]`, where a directory is an array
+//! whose first element describes the directory and the rest are its children,
+//! and a file is a single object.
+
+use crate::domain::entities::{FileStats, ScanError, SizeMode};
+use crate::domain::ports::{ScanEvent, ScanHandle, Scanner};
+use crate::domain::tree::{DirTree, NodeId};
+use crate::infrastructure::scanner::heuristics::HeuristicsEngine;
+use crate::infrastructure::scanner::progress::ProgressTracker;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const MAJOR_VERSION: u64 = 1;
+const MINOR_VERSION: u64 = 2;
+
+/// A `Scanner` that replays an ncdu export instead of walking the disk.
+pub struct NcduScanner {
+    root: PathBuf,
+    data: Arc<Value>,
+}
+
+impl NcduScanner {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open ncdu export: {}", path.display()))?;
+        Self::from_reader(BufReader::new(file))
+            .with_context(|| format!("Failed to read ncdu export: {}", path.display()))
+    }
+
+    pub fn from_reader(reader: impl Read) -> Result<Self> {
+        // Directory nesting becomes array nesting, which can run deeper than
+        // serde_json's recursion limit; grow the stack on demand instead.
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        deserializer.disable_recursion_limit();
+        let data = Value::deserialize(serde_stacker::Deserializer::new(&mut deserializer))
+            .and_then(|data| deserializer.end().map(|()| data))
+            .context("Invalid JSON")?;
+        let Some(items) = data.as_array() else {
+            bail!("Not an ncdu export: expected a top-level array");
+        };
+        if items.first().and_then(Value::as_u64) != Some(MAJOR_VERSION) {
+            bail!("Unsupported ncdu export version");
+        }
+        let root = items
+            .get(3)
+            .and_then(|root| info_of(root).get("name"))
+            .and_then(Value::as_str)
+            .context("Not an ncdu export: missing root directory")?;
+        Ok(Self {
+            root: PathBuf::from(root),
+            data: Arc::new(data),
+        })
+    }
+
+    /// Path of the scanned directory recorded in the export.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Builds the whole tree at once, along with the read errors recorded
+    /// in the export, instead of replaying it through a scan channel.
+    pub fn build_tree(&self) -> (DirTree, Vec<ScanError>) {
+        let mut tree = DirTree::new(&self.root);
+        let mut errors = Vec::new();
+        let mut replay = Replay::new(&self.root, ScanHandle::new(), |event| {
+            match event {
+                ScanEvent::NewEntry(stats) => {
+                    tree.insert(stats);
+                }
+                ScanEvent::Error(error) => {
+                    if let Some(path) = &error.path {
+                        tree.mark_incomplete(path);
+                    }
+                    errors.push(error);
+                }
+                ScanEvent::Progress(_) | ScanEvent::Complete => {}
+            }
+            true
+        });
+        replay.entry(&self.data[3], None, 0);
+        (tree, errors)
+    }
+}
+
+impl Scanner for NcduScanner {
+    fn scan(&self, root: &Path, tx: Sender<ScanEvent>) -> ScanHandle {
+        let handle = ScanHandle::new();
+        let data = self.data.clone();
+        let mut replay = Replay::new(root, handle.clone(), move |event| tx.send(event).is_ok());
+
+        thread::spawn(move || {
+            if replay.entry(&data[3], None, 0) {
+                replay.tracker.set_excluded(replay.excluded);
+                let snapshot = replay.tracker.snapshot();
+                (replay.emit)(ScanEvent::Progress(snapshot));
+            }
+            (replay.emit)(ScanEvent::Complete);
+        });
+        handle
+    }
+}
+
+/// State of one replay of an export, limited to the subtree at `target`.
+/// Events go to `emit`, which returns false once nobody wants more.
+struct Replay<F> {
+    target: PathBuf,
+    emit: F,
+    handle: ScanHandle,
+    heuristics: HeuristicsEngine,
+    tracker: ProgressTracker,
+    seen_inodes: HashSet<(u64, u64)>,
+    excluded: usize,
+}
+
+impl<F: FnMut(ScanEvent) -> bool> Replay<F> {
+    fn new(target: &Path, handle: ScanHandle, emit: F) -> Self {
+        Self {
+            target: target.to_path_buf(),
+            emit,
+            handle,
+            heuristics: HeuristicsEngine::new(),
+            tracker: ProgressTracker::new(None),
+            seen_inodes: HashSet::new(),
+            excluded: 0,
+        }
+    }
+
+    /// Emits `item` and everything below it. Returns false once the scan
+    /// was cancelled or the receiver went away.
+    fn entry(&mut self, item: &Value, parent: Option<(&Path, u64)>, depth: usize) -> bool {
+        if !self.handle.checkpoint() {
+            return false;
+        }
+        let info = info_of(item);
+        let Some(name) = info.get("name").and_then(Value::as_str) else {
+            return true;
+        };
+        let path = match parent {
+            Some((parent, _)) => parent.join(name),
+            None => PathBuf::from(name),
+        };
+        // Only walk towards and below the requested subtree.
+        if !path.starts_with(&self.target) && !self.target.starts_with(&path) {
+            return true;
+        }
+        if info.get("excluded").is_some_and(is_set) {
+            if path.starts_with(&self.target) {
+                self.excluded += 1;
+            }
+            return true;
+        }
+
+        let is_dir = item.is_array();
+        let parent_dev = parent.map_or(0, |(_, dev)| dev);
+        let dev = number(info, "dev").unwrap_or(parent_dev);
+
+        if path.starts_with(&self.target) {
+            let hardlinked = info.get("hlnkc").is_some_and(is_set);
+            let links = number(info, "nlink").unwrap_or(if hardlinked { 2 } else { 1 });
+            let inode = number(info, "ino")
+                .filter(|_| !is_dir && (hardlinked || links > 1))
+                .map(|ino| (dev, ino));
+            let duplicate_inode = inode.is_some_and(|inode| !self.seen_inodes.insert(inode));
+            let allocated_size = number(info, "dsize").unwrap_or(0);
+            if !duplicate_inode {
+                self.tracker.record_entry(&path, is_dir, allocated_size);
+            }
+
+            let stats = FileStats {
+                path: path.clone(),
+                size: number(info, "asize").unwrap_or(0),
+                allocated_size,
+                is_dir,
+                modified: number(info, "mtime")
+                    .map(|secs| UNIX_EPOCH + Duration::from_secs(secs))
+                    .unwrap_or(UNIX_EPOCH),
+                children_count: None,
+                file_type: self.heuristics.analyze(&path, is_dir),
+                links,
+                duplicate_inode,
+                is_mount_point: is_dir && depth > 0 && dev != parent_dev,
+                inode,
+            };
+            if !(self.emit)(ScanEvent::NewEntry(stats)) {
+                return false;
+            }
+
+            if info.get("read_error").is_some_and(is_set) {
+                self.tracker.record_error();
+                let error = ScanError::other(Some(path.clone()), "read error recorded in export");
+                (self.emit)(ScanEvent::Error(error));
+            }
+            if let Some(snapshot) = self.tracker.due() {
+                (self.emit)(ScanEvent::Progress(snapshot));
+            }
+        }
+
+        if let Some(children) = item.as_array() {
+            for child in children.iter().skip(1) {
+                if !self.entry(child, Some((&path, dev)), depth + 1) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Writes `tree` as an ncdu export. Entries whose path appears in `errors`
+/// are flagged with `read_error`.
+pub fn export(tree: &DirTree, errors: &[ScanError], mut out: impl Write) -> Result<()> {
+    let mut writer = Export {
+        tree,
+        error_paths: errors.iter().filter_map(|e| e.path.as_deref()).collect(),
+        next_dev: 0,
+    };
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let metadata = json!({
+        "progname": "diskdoc",
+        "progver": env!("CARGO_PKG_VERSION"),
+        "timestamp": timestamp,
+    });
+    write!(out, "[{},{},{},", MAJOR_VERSION, MINOR_VERSION, metadata)?;
+    writer.node(&mut out, tree.root(), 0)?;
+    writeln!(out, "]")?;
+    out.flush()?;
+    Ok(())
+}
+
+struct Export<'a> {
+    tree: &'a DirTree,
+    error_paths: HashSet<&'a Path>,
+    /// Last synthetic device number handed out. Real device numbers mean
+    /// nothing on another machine, so each mount point gets a new one.
+    next_dev: u64,
+}
+
+impl Export<'_> {
+    fn node(&mut self, out: &mut impl Write, id: NodeId, dev: u64) -> Result<()> {
+        let tree = self.tree;
+        let node = tree.node(id);
+        let path = tree.path(id);
+        let mut info = Map::new();
+        let name = if id == tree.root() {
+            path.to_string_lossy().into_owned()
+        } else {
+            tree.name(id).to_string_lossy().into_owned()
+        };
+        info.insert("name".into(), name.into());
+        info.insert("asize".into(), node.own_size(SizeMode::Apparent).into());
+        info.insert("dsize".into(), node.own_size(SizeMode::Disk).into());
+
+        let mut dev = dev;
+        if node.is_mount_point && id != tree.root() {
+            self.next_dev += 1;
+            dev = self.next_dev;
+            info.insert("dev".into(), dev.into());
+        }
+        if let Some((_, ino)) = node.inode {
+            info.insert("ino".into(), ino.into());
+            info.insert("hlnkc".into(), true.into());
+            info.insert("nlink".into(), node.links.into());
+        }
+        if let Ok(mtime) = node.modified.duration_since(UNIX_EPOCH) {
+            if mtime.as_secs() > 0 {
+                info.insert("mtime".into(), mtime.as_secs().into());
+            }
+        }
+        if self.error_paths.contains(path.as_path()) {
+            info.insert("read_error".into(), true.into());
+        }
+
+        if !node.is_dir {
+            write!(out, "{}", Value::Object(info))?;
+            return Ok(());
+        }
+        write!(out, "[{}", Value::Object(info))?;
+        for &child in tree.children(id) {
+            write!(out, ",")?;
+            self.node(out, child, dev)?;
+        }
+        write!(out, "]")?;
+        Ok(())
+    }
+}
+
+/// The info object of an entry: the entry itself for files, the first
+/// element for directories.
+fn info_of(item: &Value) -> &Map<String, Value> {
+    static EMPTY: std::sync::OnceLock<Map<String, Value>> = std::sync::OnceLock::new();
+    let info = match item {
+        Value::Array(items) => items.first(),
+        other => Some(other),
+    };
+    info.and_then(Value::as_object)
+        .unwrap_or_else(|| EMPTY.get_or_init(Map::new))
+}
+
+fn number(info: &Map<String, Value>, key: &str) -> Option<u64> {
+    info.get(key).and_then(Value::as_u64)
+}
+
+/// Flags are `true` in current exports; `excluded` is a reason string.
+fn is_set(value: &Value) -> bool {
+    match value {
+        Value::Bool(flag) => *flag,
+        Value::String(_) => true,
+        _ => false,
+    }
+}
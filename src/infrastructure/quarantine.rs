@@ -0,0 +1,408 @@
+//! Deletion by moving items aside into a quarantine area, so they can be
+//! restored with `undo` until they are purged.
+//!
+//! Items are renamed, never copied: those on the home filesystem go under
+//! `$XDG_DATA_HOME/diskdoc/quarantine`, those on other volumes under
+//! `$topdir/.diskdoc-quarantine-$uid`. A journal next to the history file
+//! remembers where each item came from, oldest first.
+
+use super::trash::{
+    absolute, create_private_dir, device_of, existing_ancestor, item_size, mount_root, user_id,
+};
+use crate::domain::entities::{AuditEntry, Removal};
+use crate::domain::ports::{AuditLog, Cleaner};
+use crate::utils::format_size;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// When quarantined items are purged for good.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PurgePolicy {
+    /// Items older than this are purged.
+    pub max_age: Option<Duration>,
+    /// Oldest items are purged until the quarantine fits in this many bytes.
+    pub max_size: Option<u64>,
+}
+
+impl Default for PurgePolicy {
+    fn default() -> Self {
+        Self {
+            max_age: Some(Duration::from_secs(7 * 86_400)),
+            max_size: None,
+        }
+    }
+}
+
+/// One quarantined item.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuarantineEntry {
+    /// Where the item was deleted from.
+    pub original: PathBuf,
+    /// Where it is kept until restored or purged.
+    pub stored: PathBuf,
+    pub size: u64,
+    /// Seconds since the epoch.
+    pub time: u64,
+    /// Entries sharing an id were deleted by one operation and are undone
+    /// together. 0 in journals written before operations were recorded.
+    #[serde(default)]
+    pub operation: u64,
+}
+
+pub struct QuarantineCleaner {
+    home_area: PathBuf,
+    journal: PathBuf,
+    policy: PurgePolicy,
+    /// Id of the operation in progress; 0 makes each deletion its own.
+    operation: AtomicU64,
+    /// Where purges are recorded, as they delete for good.
+    audit: Option<Box<dyn AuditLog>>,
+}
+
+impl QuarantineCleaner {
+    /// Quarantine under `$XDG_DATA_HOME/diskdoc`, falling back to
+    /// `~/.local/share/diskdoc`.
+    pub fn new(policy: PurgePolicy) -> Result<Self> {
+        let data = env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| {
+                env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+            })
+            .context("Cannot locate the data directory: neither XDG_DATA_HOME nor HOME is set")?
+            .join("diskdoc");
+        Ok(Self::with_paths(
+            data.join("quarantine"),
+            data.join("quarantine.jsonl"),
+            policy,
+        ))
+    }
+
+    pub fn with_paths(
+        home_area: impl Into<PathBuf>,
+        journal: impl Into<PathBuf>,
+        policy: PurgePolicy,
+    ) -> Self {
+        Self {
+            home_area: home_area.into(),
+            journal: journal.into(),
+            policy,
+            operation: AtomicU64::new(0),
+            audit: None,
+        }
+    }
+
+    /// Records every purged item in `audit`.
+    pub fn with_audit(mut self, audit: Box<dyn AuditLog>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// The entries `undo(count)` would restore, newest first.
+    pub fn pending_undo(&self, count: usize) -> Result<Vec<QuarantineEntry>> {
+        let mut entries = self.entries()?;
+        let start = operations_start(&entries, count);
+        let mut pending = entries.split_off(start);
+        pending.reverse();
+        Ok(pending)
+    }
+
+    /// Quarantined items, oldest first.
+    pub fn entries(&self) -> Result<Vec<QuarantineEntry>> {
+        let file = match File::open(&self.journal) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!("Failed to open quarantine: {}", self.journal.display())
+                })
+            }
+        };
+        Ok(BufReader::new(file)
+            .lines()
+            .map_while(|line| line.ok())
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect())
+    }
+
+    /// Removes for good whatever the policy no longer keeps, and returns it.
+    pub fn purge(&self) -> Result<Vec<QuarantineEntry>> {
+        self.purge_at(SystemTime::now())
+    }
+
+    /// [`purge`](Self::purge) as of `now`.
+    pub fn purge_at(&self, now: SystemTime) -> Result<Vec<QuarantineEntry>> {
+        self.purge_sparing(now, None)
+    }
+
+    /// Purges as of `now`, sparing the items of `operation`: it is still in
+    /// progress, and what it deleted must stay undoable.
+    fn purge_sparing(
+        &self,
+        now: SystemTime,
+        operation: Option<u64>,
+    ) -> Result<Vec<QuarantineEntry>> {
+        let now = now.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let entries = self.entries()?;
+        let mut total: u64 = entries.iter().map(|entry| entry.size).sum();
+        let mut entries = entries.into_iter();
+        let mut kept = Vec::new();
+        let mut purged = Vec::new();
+        let mut failures = Vec::new();
+        // Entries are oldest first, so purging from the front satisfies both
+        // the age limit and the size budget. Items that cannot be removed
+        // stay in the journal, and the purge goes on past them.
+        for entry in entries.by_ref() {
+            // The operation's items are the newest, so nothing after them
+            // is purged either.
+            if operation == Some(entry.operation) {
+                kept.push(entry);
+                break;
+            }
+            let expired = self
+                .policy
+                .max_age
+                .is_some_and(|age| now.saturating_sub(entry.time) > age.as_secs());
+            let over_budget = self.policy.max_size.is_some_and(|max| total > max);
+            if !expired && !over_budget {
+                kept.push(entry);
+                break;
+            }
+            let outcome = remove_stored(&entry.stored);
+            self.record(&entry, &outcome);
+            match outcome {
+                Ok(()) => {
+                    total -= entry.size;
+                    purged.push(entry);
+                }
+                Err(err) => {
+                    failures.push(err);
+                    kept.push(entry);
+                }
+            }
+        }
+        kept.extend(entries);
+        if !purged.is_empty() {
+            self.save(&kept)?;
+        }
+        let count = failures.len();
+        if let Some(err) = failures.into_iter().next() {
+            return Err(err.context(format!("{} quarantined items could not be purged", count)));
+        }
+        Ok(purged)
+    }
+
+    /// Notes a purge in the audit log, if there is one.
+    fn record(&self, entry: &QuarantineEntry, outcome: &Result<()>) {
+        if let Some(audit) = &self.audit {
+            let _ = audit.append(AuditEntry {
+                target: entry.original.display().to_string(),
+                size: Some(entry.size),
+                file_type: None,
+                recommendation: None,
+                removal: Removal::Purge,
+                outcome: match outcome {
+                    Ok(()) => Ok(entry.size),
+                    Err(e) => Err(e.to_string()),
+                },
+            });
+        }
+    }
+
+    /// The quarantine area on the filesystem of `path`, so moving there is
+    /// a rename.
+    fn area_for(&self, path: &Path) -> Result<PathBuf> {
+        let device = device_of(path)?;
+        if device_of(existing_ancestor(&self.home_area)).ok() == Some(device) {
+            return Ok(self.home_area.clone());
+        }
+        let topdir = mount_root(path, device)?;
+        Ok(topdir.join(format!(".diskdoc-quarantine-{}", user_id())))
+    }
+
+    fn append(&self, entry: &QuarantineEntry) -> Result<()> {
+        if let Some(dir) = self.journal.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.journal)
+            .with_context(|| format!("Failed to open quarantine: {}", self.journal.display()))?;
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("Failed to write quarantine: {}", self.journal.display()))
+    }
+
+    /// Rewrites the journal, replacing it in one rename.
+    fn save(&self, entries: &[QuarantineEntry]) -> Result<()> {
+        let tmp = self.journal.with_extension("jsonl.tmp");
+        let mut out = String::new();
+        for entry in entries {
+            out.push_str(&serde_json::to_string(entry)?);
+            out.push('\n');
+        }
+        fs::write(&tmp, out).with_context(|| format!("Failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, &self.journal)
+            .with_context(|| format!("Failed to write quarantine: {}", self.journal.display()))
+    }
+}
+
+impl Cleaner for QuarantineCleaner {
+    fn delete_item(&self, path: &Path) -> Result<u64> {
+        let path = absolute(path)?;
+        let name = path
+            .file_name()
+            .context("Cannot quarantine a path without a file name")?;
+        let size = item_size(&path).context("Failed to read metadata")?;
+        // It would be purged as soon as it arrived, with no chance to undo.
+        if let Some(max) = self.policy.max_size.filter(|&max| size > max) {
+            bail!(
+                "{} is over the quarantine limit of {}; use --no-quarantine to delete it for good",
+                format_size(size),
+                format_size(max)
+            );
+        }
+
+        let area = self.area_for(&path)?;
+        create_private_dir(&area).context("Failed to create the quarantine directory")?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let slot = reserve_slot(&area, now.as_nanos())?;
+        let stored = slot.join(name);
+        if let Err(err) = fs::rename(&path, &stored) {
+            let _ = fs::remove_dir(&slot);
+            return Err(err).context("Failed to move the item to the quarantine");
+        }
+
+        let operation = match self.operation.load(Ordering::SeqCst) {
+            0 => now.as_nanos() as u64,
+            operation => operation,
+        };
+        let entry = QuarantineEntry {
+            original: path,
+            stored,
+            size,
+            time: now.as_secs(),
+            operation,
+        };
+        // Without a journal entry the item could never be restored, so put
+        // it back rather than leave it stranded.
+        if let Err(err) = self.append(&entry) {
+            if fs::rename(&entry.stored, &entry.original).is_ok() {
+                let _ = fs::remove_dir(&slot);
+            }
+            return Err(err);
+        }
+        // Keep within the budget; a failed purge must not fail the deletion.
+        let _ = self.purge_sparing(SystemTime::now(), Some(operation));
+        Ok(size)
+    }
+
+    fn removal(&self) -> Removal {
+        Removal::Quarantine
+    }
+
+    fn begin_operation(&self) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        self.operation
+            .store(now.as_nanos() as u64, Ordering::SeqCst);
+    }
+
+    fn undo(&self, count: usize) -> Result<Vec<PathBuf>> {
+        let mut entries = self.entries()?;
+        let start = operations_start(&entries, count);
+        let mut restored = Vec::new();
+        let mut result = Ok(());
+        while entries.len() > start {
+            let Some(entry) = entries.pop() else { break };
+            if let Err(err) = restore(&entry) {
+                entries.push(entry);
+                result = Err(err);
+                break;
+            }
+            restored.push(entry.original);
+        }
+        if !restored.is_empty() {
+            self.save(&entries)?;
+        }
+        result.map(|()| restored)
+    }
+}
+
+/// Index of the first entry in the last `count` operations. Entries
+/// without an operation count as one each.
+fn operations_start(entries: &[QuarantineEntry], count: usize) -> usize {
+    let mut start = entries.len();
+    for _ in 0..count {
+        let Some(operation) = start.checked_sub(1).map(|last| entries[last].operation) else {
+            break;
+        };
+        start -= 1;
+        if operation != 0 {
+            while start > 0 && entries[start - 1].operation == operation {
+                start -= 1;
+            }
+        }
+    }
+    start
+}
+
+/// Creates an empty directory for one item, named after the time so that
+/// two items with the same name never collide.
+fn reserve_slot(area: &Path, nanos: u128) -> Result<PathBuf> {
+    for n in 0.. {
+        let slot = area.join(format!("{}-{}", nanos, n));
+        match fs::create_dir(&slot) {
+            Ok(()) => return Ok(slot),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err).context("Failed to create the quarantine directory"),
+        }
+    }
+    unreachable!("ran out of quarantine slots")
+}
+
+fn restore(entry: &QuarantineEntry) -> Result<()> {
+    if fs::symlink_metadata(&entry.original).is_ok() {
+        anyhow::bail!(
+            "Cannot restore {}: something else is there now",
+            entry.original.display()
+        );
+    }
+    if let Some(parent) = entry.original.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::rename(&entry.stored, &entry.original)
+        .with_context(|| format!("Failed to restore {}", entry.original.display()))?;
+    if let Some(slot) = entry.stored.parent() {
+        let _ = fs::remove_dir(slot);
+    }
+    Ok(())
+}
+
+/// Deletes a quarantined item and its slot. Items already gone are fine.
+fn remove_stored(stored: &Path) -> Result<()> {
+    let removed = match fs::symlink_metadata(stored) {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(stored),
+        Ok(_) => fs::remove_file(stored),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    };
+    removed.with_context(|| format!("Failed to purge {}", stored.display()))?;
+    if let Some(slot) = stored.parent() {
+        let _ = fs::remove_dir(slot);
+    }
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Ignore files honored when `ScanOptions::use_ignore_files` is set.
+pub const IGNORE_FILES: [&str; 3] = [".gitignore", ".ignore", ".diskdocignore"];
+
+/// Matches paths against the `--exclude` glob patterns.
+///
+/// A pattern matches if it matches the entry's file name, its path relative
+/// to the scan root, or its full path, so `node_modules`, `build/cache` and
+/// `/var/tmp/*` all work as expected.
+pub struct ExcludeMatcher {
+    root: PathBuf,
+    globs: GlobSet,
+}
+
+impl ExcludeMatcher {
+    pub fn new(root: &Path, patterns: &[String]) -> Result<Self> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let glob = Glob::new(pattern)
+                .with_context(|| format!("Invalid exclude pattern: {}", pattern))?;
+            builder.add(glob);
+        }
+        Ok(Self {
+            root: root.to_path_buf(),
+            globs: builder
+                .build()
+                .context("Failed to build exclude patterns")?,
+        })
+    }
+
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        if self.globs.is_empty() {
+            return false;
+        }
+        path.file_name()
+            .is_some_and(|name| self.globs.is_match(name))
+            || path
+                .strip_prefix(&self.root)
+                .is_ok_and(|relative| self.globs.is_match(relative))
+            || self.globs.is_match(path)
+    }
+}
+
+/// Ignore-file matchers in effect for a directory, outermost first.
+/// Cloned into every child directory as the walk descends.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreStack {
+    matchers: Vec<Arc<Gitignore>>,
+}
+
+impl IgnoreStack {
+    /// Adds the ignore files present in `dir`, given the names of its children.
+    pub fn enter<'a>(&mut self, dir: &Path, names: impl Iterator<Item = &'a OsStr>) {
+        let mut builder = GitignoreBuilder::new(dir);
+        let mut found = false;
+        for name in names {
+            if IGNORE_FILES.iter().any(|f| OsStr::new(f) == name) {
+                // A malformed line should not abort the scan; skip it.
+                let _ = builder.add(dir.join(name));
+                found = true;
+            }
+        }
+        if found {
+            if let Ok(matcher) = builder.build() {
+                self.matchers.push(Arc::new(matcher));
+            }
+        }
+    }
+
+    /// Whether the innermost ignore file with an opinion ignores `path`.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        for matcher in self.matchers.iter().rev() {
+            match matcher.matched(path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        false
+    }
+}
+
+/// Reads exclude patterns from a file, one per line. Blank lines and lines
+/// starting with `#` are skipped.
+pub fn read_patterns(path: &Path) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read exclude file: {}", path.display()))?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect())
+}
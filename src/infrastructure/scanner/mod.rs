@@ -1,15 +1,25 @@
+pub mod exclude;
 pub mod heuristics;
+pub mod progress;
 pub mod walker;
 
-use crate::domain::ports::{ScanEvent, Scanner};
+use crate::domain::entities::ScanOptions;
+use crate::domain::ports::{ScanEvent, ScanHandle, Scanner};
+use std::collections::HashSet;
 use std::path::Path;
 use std::sync::mpsc::Sender;
 
-pub struct FsScanner;
+pub struct FsScanner {
+    options: ScanOptions,
+}
 
 impl FsScanner {
     pub fn new() -> Self {
-        Self
+        Self::with_options(ScanOptions::default())
+    }
+
+    pub fn with_options(options: ScanOptions) -> Self {
+        Self { options }
     }
 }
 
@@ -20,7 +30,18 @@ impl Default for FsScanner {
 }
 
 impl Scanner for FsScanner {
-    fn scan(&self, root: &Path, tx: Sender<ScanEvent>) {
-        walker::start_scan(root, tx);
+    fn scan(&self, root: &Path, tx: Sender<ScanEvent>) -> ScanHandle {
+        self.rescan(root, HashSet::new(), tx)
+    }
+
+    fn rescan(
+        &self,
+        root: &Path,
+        counted: HashSet<(u64, u64)>,
+        tx: Sender<ScanEvent>,
+    ) -> ScanHandle {
+        let handle = ScanHandle::new();
+        walker::start_scan(root, self.options.clone(), counted, handle.clone(), tx);
+        handle
     }
 }
@@ -0,0 +1,90 @@
+use crate::domain::entities::ScanProgress;
+use crate::domain::history::FilesystemSpace;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use sysinfo::Disks;
+
+const EMIT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Accumulates scan statistics and decides when a progress event is due.
+pub struct ProgressTracker {
+    started: Instant,
+    last_emit: Instant,
+    progress: ScanProgress,
+}
+
+impl ProgressTracker {
+    pub fn new(expected_bytes: Option<u64>) -> Self {
+        let now = Instant::now();
+        Self {
+            started: now,
+            last_emit: now,
+            progress: ScanProgress {
+                expected_bytes,
+                ..ScanProgress::default()
+            },
+        }
+    }
+
+    pub fn record_entry(&mut self, path: &Path, is_dir: bool, size: u64) {
+        self.progress.bytes_seen += size;
+        self.progress.entries_seen += 1;
+        let dir = if is_dir { Some(path) } else { path.parent() };
+        if let Some(dir) = dir {
+            if self.progress.current_dir.as_deref() != Some(dir) {
+                self.progress.current_dir = Some(dir.to_path_buf());
+            }
+        }
+    }
+
+    pub fn record_error(&mut self) {
+        self.progress.errors += 1;
+    }
+
+    pub fn set_excluded(&mut self, excluded: usize) {
+        self.progress.excluded = excluded;
+    }
+
+    /// Returns a snapshot if enough time passed since the last one.
+    pub fn due(&mut self) -> Option<ScanProgress> {
+        if self.last_emit.elapsed() < EMIT_INTERVAL {
+            return None;
+        }
+        self.last_emit = Instant::now();
+        Some(self.snapshot())
+    }
+
+    pub fn snapshot(&self) -> ScanProgress {
+        ScanProgress {
+            elapsed: self.started.elapsed(),
+            ..self.progress.clone()
+        }
+    }
+}
+
+/// Capacity and free space of the filesystem holding `path`.
+pub fn filesystem_space(path: &Path) -> Option<FilesystemSpace> {
+    let path = path.canonicalize().ok()?;
+    let disks = Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| FilesystemSpace {
+            total: disk.total_space(),
+            available: disk.available_space(),
+        })
+}
+
+/// Used bytes of the filesystem mounted at `root`. Only known when the scan
+/// starts at a mount point, since a subdirectory holds an unknown share of it.
+pub fn filesystem_used_bytes(root: &Path) -> Option<u64> {
+    let root = root.canonicalize().ok()?;
+    let disks = Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .find(|disk| disk.mount_point() == root)
+        .map(|disk| disk.total_space().saturating_sub(disk.available_space()))
+}
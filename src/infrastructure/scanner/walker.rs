@@ -1,54 +1,222 @@
-use crate::domain::entities::FileStats;
-use crate::domain::ports::ScanEvent;
-use jwalk::{Parallelism, WalkDir};
+use crate::domain::entities::{FileStats, ScanError, ScanOptions};
+use crate::domain::ports::{ScanEvent, ScanHandle};
+use crate::infrastructure::scanner::exclude::{ExcludeMatcher, IgnoreStack};
+use crate::infrastructure::scanner::progress::{self, ProgressTracker};
+use jwalk::{Parallelism, WalkDirGeneric};
+use std::collections::HashSet;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::Sender;
+use std::sync::Arc;
 use std::thread;
 use std::time::SystemTime;
 
-pub fn start_scan(root: &Path, tx: Sender<ScanEvent>) {
+/// Walker state: the ignore files in effect for each directory read, and
+/// whether each entry is a mount point.
+type WalkState = (IgnoreStack, bool);
+
+/// Walks `root` on a background thread. Hard-linked inodes in `counted` are
+/// treated as already counted.
+pub fn start_scan(
+    root: &Path,
+    options: ScanOptions,
+    counted: HashSet<(u64, u64)>,
+    handle: ScanHandle,
+    tx: Sender<ScanEvent>,
+) {
     let root_path = root.to_path_buf();
 
     thread::spawn(move || {
-        let walk = WalkDir::new(&root_path)
+        let root_device = std::fs::metadata(&root_path)
+            .ok()
+            .and_then(|m| device_of(&m));
+        let root_is_mount_point = match (root_path.canonicalize().ok(), root_device) {
+            (Some(canonical), Some(device)) => canonical
+                .parent()
+                .and_then(|parent| std::fs::metadata(parent).ok())
+                .and_then(|m| device_of(&m))
+                .is_some_and(|parent_device| parent_device != device),
+            _ => false,
+        };
+        let one_file_system = options.one_file_system;
+        let use_ignore_files = options.use_ignore_files;
+        let excludes = match ExcludeMatcher::new(&root_path, &options.excludes) {
+            Ok(excludes) => excludes,
+            Err(e) => {
+                let _ = tx.send(ScanEvent::Error(ScanError::other(None, format!("{:#}", e))));
+                let _ = tx.send(ScanEvent::Complete);
+                return;
+            }
+        };
+        let excluded = Arc::new(AtomicUsize::new(0));
+        let excluded_counter = excluded.clone();
+
+        let walk = WalkDirGeneric::<WalkState>::new(&root_path)
             .skip_hidden(false)
             .sort(true)
-            .parallelism(Parallelism::RayonNewPool(4));
+            .parallelism(Parallelism::RayonNewPool(4))
+            .process_read_dir(move |_depth, dir, ignores, children| {
+                if use_ignore_files {
+                    ignores.enter(
+                        dir,
+                        children.iter().flatten().map(|c| c.file_name.as_os_str()),
+                    );
+                }
+                // Prune excluded entries here so excluded directories are never read.
+                let before = children.len();
+                children.retain(|child| match child {
+                    Ok(child) => {
+                        let path = child.path();
+                        !(excludes.is_excluded(&path)
+                            || ignores.is_ignored(&path, child.file_type.is_dir()))
+                    }
+                    Err(_) => true,
+                });
+                excluded_counter.fetch_add(before - children.len(), Ordering::Relaxed);
+
+                let parent_device = std::fs::metadata(dir).ok().and_then(|m| device_of(&m));
+                for child in children.iter_mut().flatten() {
+                    if !child.file_type.is_dir() {
+                        continue;
+                    }
+                    let device = child.metadata().ok().and_then(|m| device_of(&m));
+                    if device.is_some() && device != parent_device {
+                        child.client_state = true;
+                        // Record the mount point but do not walk into it.
+                        if one_file_system && device != root_device {
+                            child.read_children_path = None;
+                        }
+                    }
+                }
+            });
 
         let heuristics_engine = crate::infrastructure::scanner::heuristics::HeuristicsEngine::new();
+        let mut tracker = ProgressTracker::new(progress::filesystem_used_bytes(&root_path));
+        // (device, inode) pairs of hardlinked files already counted.
+        let mut seen_inodes = counted;
 
         for entry in walk {
+            if !handle.checkpoint() {
+                break;
+            }
             match entry {
                 Ok(dir_entry) => {
                     let path = dir_entry.path();
+                    let is_mount_point = if dir_entry.depth == 0 {
+                        root_is_mount_point
+                    } else {
+                        dir_entry.client_state
+                    };
                     let metadata = dir_entry.metadata();
+                    // Reported after the entry itself so the directory exists in the tree.
+                    let read_error = dir_entry
+                        .read_children_error
+                        .as_ref()
+                        .map(|e| to_scan_error(e, Some(&path)));
+
+                    match metadata {
+                        Ok(meta) => {
+                            let size = meta.len();
+                            let allocated_size = allocated_size(&meta);
+                            let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                            let is_dir = dir_entry.file_type.is_dir();
+                            let file_type = heuristics_engine.analyze(&path, is_dir);
+                            let (links, inode) = link_info(&meta);
+                            let duplicate_inode = match inode {
+                                Some(inode) if !is_dir && links > 1 => !seen_inodes.insert(inode),
+                                _ => false,
+                            };
+                            if !duplicate_inode {
+                                tracker.record_entry(&path, is_dir, allocated_size);
+                            }
+
+                            let stats = FileStats {
+                                path: path.clone(),
+                                size,
+                                allocated_size,
+                                is_dir,
+                                modified,
+                                children_count: None,
+                                file_type,
+                                links,
+                                duplicate_inode,
+                                is_mount_point,
+                                inode: inode.filter(|_| !is_dir && links > 1),
+                            };
 
-                    if let Ok(meta) = metadata {
-                        let size = meta.len();
-                        let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
-                        let is_dir = dir_entry.file_type.is_dir();
-                        let file_type = heuristics_engine.analyze(&path, is_dir);
-
-                        let stats = FileStats {
-                            path,
-                            size,
-                            is_dir,
-                            modified,
-                            children_count: None,
-                            file_type,
-                        };
-
-                        if tx.send(ScanEvent::NewEntry(stats)).is_err() {
-                            break; // Receiver dropped
+                            if tx.send(ScanEvent::NewEntry(stats)).is_err() {
+                                break; // Receiver dropped
+                            }
                         }
+                        Err(e) => {
+                            tracker.record_error();
+                            let _ = tx.send(ScanEvent::Error(to_scan_error(&e, Some(&path))));
+                        }
+                    }
+
+                    if let Some(error) = read_error {
+                        tracker.record_error();
+                        let _ = tx.send(ScanEvent::Error(error));
                     }
                 }
                 Err(e) => {
-                    let _ = tx.send(ScanEvent::Error(e.to_string()));
+                    tracker.record_error();
+                    let _ = tx.send(ScanEvent::Error(to_scan_error(&e, None)));
+                }
+            }
+
+            tracker.set_excluded(excluded.load(Ordering::Relaxed));
+            if let Some(snapshot) = tracker.due() {
+                if tx.send(ScanEvent::Progress(snapshot)).is_err() {
+                    break;
                 }
             }
         }
 
+        tracker.set_excluded(excluded.load(Ordering::Relaxed));
+        let _ = tx.send(ScanEvent::Progress(tracker.snapshot()));
         let _ = tx.send(ScanEvent::Complete);
     });
 }
+
+#[cfg(unix)]
+pub(crate) fn allocated_size(meta: &std::fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    meta.blocks() * 512
+}
+
+#[cfg(not(unix))]
+pub(crate) fn allocated_size(meta: &std::fs::Metadata) -> u64 {
+    meta.len()
+}
+
+fn to_scan_error(err: &jwalk::Error, fallback: Option<&Path>) -> ScanError {
+    let path = err.path().or(fallback).map(Path::to_path_buf);
+    match err.io_error() {
+        Some(io_error) => ScanError::from_io(path, io_error),
+        None => ScanError::other(path, err.to_string()),
+    }
+}
+
+#[cfg(unix)]
+fn device_of(meta: &std::fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(meta.dev())
+}
+
+#[cfg(not(unix))]
+fn device_of(_meta: &std::fs::Metadata) -> Option<u64> {
+    None
+}
+
+/// Hard link count and (device, inode) identity of an entry.
+#[cfg(unix)]
+fn link_info(meta: &std::fs::Metadata) -> (u64, Option<(u64, u64)>) {
+    use std::os::unix::fs::MetadataExt;
+    (meta.nlink(), Some((meta.dev(), meta.ino())))
+}
+
+#[cfg(not(unix))]
+fn link_info(_meta: &std::fs::Metadata) -> (u64, Option<(u64, u64)>) {
+    (1, None)
+}
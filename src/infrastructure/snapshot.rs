@@ -0,0 +1,236 @@
+//! Saved scans under the user's cache directory.
+//!
+//! A snapshot is a gzip stream holding one JSON header line with the
+//! [`SnapshotMeta`], followed by the tree in ncdu export format.
+
+use crate::domain::entities::{ScanError, ScanOptions, SizeMode, SnapshotMeta};
+use crate::domain::tree::DirTree;
+use crate::infrastructure::ncdu::{self, NcduScanner};
+use anyhow::{bail, Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const FORMAT: &str = "diskdoc-snapshot";
+const VERSION: u32 = 1;
+const EXTENSION: &str = "snap";
+
+pub struct SnapshotStore {
+    dir: PathBuf,
+}
+
+impl SnapshotStore {
+    /// Store in `$XDG_CACHE_HOME/diskdoc/snapshots`, falling back to
+    /// `~/.cache/diskdoc/snapshots`.
+    pub fn new() -> Result<Self> {
+        let cache = env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+            .context("Cannot locate the cache directory: neither XDG_CACHE_HOME nor HOME is set")?;
+        Ok(Self::with_dir(cache.join("diskdoc").join("snapshots")))
+    }
+
+    pub fn with_dir(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Writes `tree` to a new snapshot file and returns its path.
+    pub fn save(
+        &self,
+        tree: &DirTree,
+        errors: &[ScanError],
+        options: &ScanOptions,
+    ) -> Result<PathBuf> {
+        let root = tree.node(tree.root());
+        let meta = SnapshotMeta {
+            root: tree.root_path().to_path_buf(),
+            created: SystemTime::now(),
+            host: sysinfo::System::host_name().unwrap_or_default(),
+            options: options.clone(),
+            entries: tree.len(),
+            apparent_size: root.size(SizeMode::Apparent),
+            allocated_size: root.size(SizeMode::Disk),
+            errors: errors.len(),
+        };
+
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let stem = format!("{}-{}", file_stem(&meta.root), unix_seconds(meta.created));
+        let mut path = self.dir.join(format!("{}.{}", stem, EXTENSION));
+        let mut attempt = 1;
+        while path.exists() {
+            path = self.dir.join(format!("{}-{}.{}", stem, attempt, EXTENSION));
+            attempt += 1;
+        }
+        let file = File::create(&path)
+            .with_context(|| format!("Failed to create snapshot: {}", path.display()))?;
+        let mut out = GzEncoder::new(BufWriter::new(file), Compression::default());
+        serde_json::to_writer(&mut out, &Header::from(&meta))?;
+        writeln!(out)?;
+        ncdu::export(tree, errors, &mut out)?;
+        out.finish()?.flush()?;
+        Ok(path)
+    }
+
+    /// All readable snapshots in the store, newest first.
+    pub fn list(&self) -> Result<Vec<(PathBuf, SnapshotMeta)>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", self.dir.display()))
+            }
+        };
+        let mut snapshots: Vec<_> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == EXTENSION))
+            .filter_map(|path| read_meta(&path).ok().map(|meta| (path, meta)))
+            .collect();
+        snapshots.sort_by_key(|(_, meta)| std::cmp::Reverse(meta.created));
+        Ok(snapshots)
+    }
+
+    /// The newest snapshot taken of `root`.
+    pub fn latest_for(&self, root: &Path) -> Result<Option<PathBuf>> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .find(|(_, meta)| meta.root == root)
+            .map(|(path, _)| path))
+    }
+}
+
+/// Reads only the header of a snapshot.
+pub fn read_meta(path: &Path) -> Result<SnapshotMeta> {
+    let mut reader = open_stream(path)?;
+    read_header(&mut reader, path)
+}
+
+/// Opens a snapshot for browsing: its metadata and a scanner replaying it.
+pub fn open(path: &Path) -> Result<(SnapshotMeta, NcduScanner)> {
+    let mut reader = open_stream(path)?;
+    let meta = read_header(&mut reader, path)?;
+    let scanner = NcduScanner::from_reader(reader)
+        .with_context(|| format!("Corrupt snapshot: {}", path.display()))?;
+    Ok((meta, scanner))
+}
+
+/// Reads a whole snapshot into a tree at once, along with the errors its
+/// scan ran into.
+pub fn load_tree(path: &Path) -> Result<(SnapshotMeta, DirTree, Vec<ScanError>)> {
+    let (meta, scanner) = open(path)?;
+    let (tree, errors) = scanner.build_tree();
+    Ok((meta, tree, errors))
+}
+
+fn open_stream(path: &Path) -> Result<BufReader<GzDecoder<File>>> {
+    let file =
+        File::open(path).with_context(|| format!("Failed to open snapshot: {}", path.display()))?;
+    Ok(BufReader::new(GzDecoder::new(file)))
+}
+
+fn read_header(reader: &mut impl BufRead, path: &Path) -> Result<SnapshotMeta> {
+    let mut line = String::new();
+    reader
+        .read_line(&mut line)
+        .with_context(|| format!("Not a diskdoc snapshot: {}", path.display()))?;
+    let header: Header = serde_json::from_str(&line)
+        .with_context(|| format!("Not a diskdoc snapshot: {}", path.display()))?;
+    if header.format != FORMAT {
+        bail!("Not a diskdoc snapshot: {}", path.display());
+    }
+    if header.version != VERSION {
+        bail!(
+            "Unsupported snapshot version {}: {}",
+            header.version,
+            path.display()
+        );
+    }
+    Ok(header.into())
+}
+
+/// On-disk form of [`SnapshotMeta`].
+#[derive(Serialize, Deserialize)]
+struct Header {
+    format: String,
+    version: u32,
+    root: String,
+    created: u64,
+    host: String,
+    one_file_system: bool,
+    excludes: Vec<String>,
+    use_ignore_files: bool,
+    entries: usize,
+    apparent_size: u64,
+    allocated_size: u64,
+    errors: usize,
+}
+
+impl From<&SnapshotMeta> for Header {
+    fn from(meta: &SnapshotMeta) -> Self {
+        Header {
+            format: FORMAT.to_string(),
+            version: VERSION,
+            root: meta.root.to_string_lossy().into_owned(),
+            created: unix_seconds(meta.created),
+            host: meta.host.clone(),
+            one_file_system: meta.options.one_file_system,
+            excludes: meta.options.excludes.clone(),
+            use_ignore_files: meta.options.use_ignore_files,
+            entries: meta.entries,
+            apparent_size: meta.apparent_size,
+            allocated_size: meta.allocated_size,
+            errors: meta.errors,
+        }
+    }
+}
+
+impl From<Header> for SnapshotMeta {
+    fn from(header: Header) -> Self {
+        SnapshotMeta {
+            root: PathBuf::from(header.root),
+            created: UNIX_EPOCH + Duration::from_secs(header.created),
+            host: header.host,
+            options: ScanOptions {
+                one_file_system: header.one_file_system,
+                excludes: header.excludes,
+                use_ignore_files: header.use_ignore_files,
+            },
+            entries: header.entries,
+            apparent_size: header.apparent_size,
+            allocated_size: header.allocated_size,
+            errors: header.errors,
+        }
+    }
+}
+
+/// A file name fragment identifying the scan root, e.g. `home_alice`.
+fn file_stem(root: &Path) -> String {
+    let stem: String = root
+        .to_string_lossy()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    match stem.trim_matches('_') {
+        "" => "root".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
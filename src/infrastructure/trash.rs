@@ -0,0 +1,294 @@
+//! Moves items to the trash following the freedesktop.org Trash
+//! specification, so desktop file managers can list and restore them.
+//!
+//! Items on the home filesystem go to `$XDG_DATA_HOME/Trash`. Items on other
+//! volumes go to `$topdir/.Trash/$uid` when an administrator has set up a
+//! sticky `.Trash`, otherwise to `$topdir/.Trash-$uid`.
+
+use crate::domain::entities::Removal;
+use crate::domain::ports::Cleaner;
+use anyhow::{Context, Result};
+use std::env;
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Component, Path, PathBuf};
+
+pub struct TrashCleaner {
+    home_trash: PathBuf,
+}
+
+impl TrashCleaner {
+    /// Trash at `$XDG_DATA_HOME/Trash`, falling back to
+    /// `~/.local/share/Trash`.
+    pub fn new() -> Result<Self> {
+        let data = env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| {
+                env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+            })
+            .context("Cannot locate the home trash: neither XDG_DATA_HOME nor HOME is set")?;
+        Ok(Self::with_home_trash(data.join("Trash")))
+    }
+
+    pub fn with_home_trash(path: impl Into<PathBuf>) -> Self {
+        Self {
+            home_trash: path.into(),
+        }
+    }
+
+    pub fn home_trash(&self) -> &Path {
+        &self.home_trash
+    }
+
+    /// The trash directory for `path` and, for per-volume trashes, the top
+    /// directory that `Path=` entries are relative to.
+    fn trash_for(&self, path: &Path) -> Result<(PathBuf, Option<PathBuf>)> {
+        let device = device_of(path)?;
+        if device_of(existing_ancestor(&self.home_trash)).ok() == Some(device) {
+            return Ok((self.home_trash.clone(), None));
+        }
+
+        let topdir = mount_root(path, device)?;
+        let uid = user_id().to_string();
+        let admin = topdir.join(".Trash");
+        let trash = if is_admin_trash(&admin) {
+            admin.join(uid)
+        } else {
+            topdir.join(format!(".Trash-{}", uid))
+        };
+        Ok((trash, Some(topdir)))
+    }
+}
+
+impl Cleaner for TrashCleaner {
+    fn delete_item(&self, path: &Path) -> Result<u64> {
+        let path = absolute(path)?;
+        let size = item_size(&path).context("Failed to read metadata")?;
+
+        let (trash, topdir) = self.trash_for(&path)?;
+        let files = trash.join("files");
+        let info = trash.join("info");
+        create_private_dir(&files).context("Failed to create the trash directory")?;
+        create_private_dir(&info).context("Failed to create the trash directory")?;
+
+        let recorded = match &topdir {
+            Some(topdir) => path.strip_prefix(topdir).unwrap_or(&path),
+            None => &path,
+        };
+        let contents = format!(
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            url_escape(recorded),
+            deletion_date()
+        );
+
+        // Claiming the .trashinfo name first, atomically, reserves the name
+        // in files/ as well, unless an orphan there already holds it.
+        let name = path
+            .file_name()
+            .context("Cannot trash a path without a file name")?;
+        let (info_path, trashed_name) = reserve_info(&info, &files, name, &contents)?;
+        if let Err(err) = fs::rename(&path, files.join(&trashed_name)) {
+            let _ = fs::remove_file(&info_path);
+            return Err(err).context("Failed to move the item to the trash");
+        }
+        Ok(size)
+    }
+
+    fn removal(&self) -> Removal {
+        Removal::Trash
+    }
+}
+
+/// Creates `info/<name>.trashinfo`, adding a `.N` suffix to the name until
+/// it is free in both `info` and `files`, and returns the info file and the
+/// name taken.
+fn reserve_info(
+    info: &Path,
+    files: &Path,
+    name: &std::ffi::OsStr,
+    contents: &str,
+) -> Result<(PathBuf, OsString)> {
+    for n in 1.. {
+        let mut candidate = name.to_os_string();
+        if n > 1 {
+            candidate.push(format!(".{}", n));
+        }
+        let mut file_name = candidate.clone();
+        file_name.push(".trashinfo");
+        let info_path = info.join(file_name);
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&info_path)
+        {
+            Ok(_) if fs::symlink_metadata(files.join(&candidate)).is_ok() => {
+                // Left behind without its info file; renaming onto it would
+                // replace it.
+                let _ = fs::remove_file(&info_path);
+            }
+            Ok(mut file) => {
+                file.write_all(contents.as_bytes())
+                    .context("Failed to write the trash info file")?;
+                return Ok((info_path, candidate));
+            }
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err).context("Failed to create the trash info file"),
+        }
+    }
+    unreachable!("ran out of trash names")
+}
+
+/// `path` made absolute without resolving its last component, which may be
+/// a symlink to trash rather than its target.
+pub(crate) fn absolute(path: &Path) -> Result<PathBuf> {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        env::current_dir()?.join(path)
+    };
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    Ok(out)
+}
+
+/// Apparent size of `path` and everything below it.
+pub(crate) fn item_size(path: &Path) -> std::io::Result<u64> {
+    let meta = fs::symlink_metadata(path)?;
+    if !meta.is_dir() {
+        return Ok(meta.len());
+    }
+    let mut size = meta.len();
+    for entry in fs::read_dir(path)? {
+        size += item_size(&entry?.path()).unwrap_or(0);
+    }
+    Ok(size)
+}
+
+pub(crate) fn existing_ancestor(path: &Path) -> &Path {
+    path.ancestors()
+        .find(|dir| dir.exists())
+        .unwrap_or(Path::new("/"))
+}
+
+/// The highest ancestor of `path` still on `device`: its mount point.
+pub(crate) fn mount_root(path: &Path, device: u64) -> Result<PathBuf> {
+    let mut root = path;
+    while let Some(parent) = root.parent() {
+        if device_of(parent)? != device {
+            break;
+        }
+        root = parent;
+    }
+    Ok(root.to_path_buf())
+}
+
+/// A `$topdir/.Trash` usable per the spec: a real directory with the sticky
+/// bit set.
+fn is_admin_trash(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::symlink_metadata(path)
+            .map(|m| m.is_dir() && m.permissions().mode() & 0o1000 != 0)
+            .unwrap_or(false)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        false
+    }
+}
+
+pub(crate) fn create_private_dir(path: &Path) -> std::io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(path)
+}
+
+#[cfg(unix)]
+pub(crate) fn device_of(path: &Path) -> Result<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Ok(fs::symlink_metadata(path)
+        .with_context(|| format!("Failed to read metadata of {}", path.display()))?
+        .dev())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn device_of(_path: &Path) -> Result<u64> {
+    anyhow::bail!("The trash is only supported on Unix systems")
+}
+
+#[cfg(unix)]
+pub(crate) fn user_id() -> u32 {
+    // SAFETY: getuid has no preconditions and cannot fail.
+    unsafe { libc::getuid() }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn user_id() -> u32 {
+    0
+}
+
+/// Percent-encodes a path as the spec's `Path=` key expects, keeping `/`.
+fn url_escape(path: &Path) -> String {
+    let bytes: Vec<u8> = {
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            path.as_os_str().as_bytes().to_vec()
+        }
+        #[cfg(not(unix))]
+        {
+            path.to_string_lossy().into_owned().into_bytes()
+        }
+    };
+    let mut out = String::with_capacity(bytes.len());
+    for byte in bytes {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+/// The current local time as `YYYY-MM-DDThh:mm:ss`.
+fn deletion_date() -> String {
+    #[cfg(unix)]
+    {
+        // SAFETY: `time` accepts a null pointer and `localtime_r` writes
+        // only to the `tm` it is given.
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        let now = unsafe { libc::time(std::ptr::null_mut()) };
+        if !unsafe { libc::localtime_r(&now, &mut tm) }.is_null() {
+            return format!(
+                "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                tm.tm_year + 1900,
+                tm.tm_mon + 1,
+                tm.tm_mday,
+                tm.tm_hour,
+                tm.tm_min,
+                tm.tm_sec
+            );
+        }
+    }
+    // Without a local time zone, fall back to UTC.
+    crate::utils::format_date(std::time::SystemTime::now()).replace(' ', "T") + ":00"
+}
@@ -0,0 +1,49 @@
+use crate::domain::entities::{AuditEntry, AuditRecord, Removal};
+use crate::utils::{format_date, format_size};
+use std::io::{self, Write};
+
+/// What led to the removal: a dashboard cleanup, a pick in the browser, or
+/// the quarantine's purge policy.
+pub fn trigger(entry: &AuditEntry) -> String {
+    match &entry.recommendation {
+        Some(category) => format!("cleanup:{}", category.key()),
+        None if entry.removal == Removal::Purge => "quarantine".to_string(),
+        None => "browser".to_string(),
+    }
+}
+
+/// `freed 1.2 MiB`, or `failed: <reason>`.
+pub fn status(entry: &AuditEntry) -> String {
+    match &entry.outcome {
+        Ok(bytes) if entry.removal == Removal::DryRun => {
+            format!("would free {}", format_size(*bytes))
+        }
+        Ok(bytes) => format!("freed {}", format_size(*bytes)),
+        Err(e) => format!("failed: {}", e),
+    }
+}
+
+/// Prints audit records, newest first, for `diskdoc log`.
+pub fn print(records: &[AuditRecord], mut out: impl Write) -> io::Result<()> {
+    if records.is_empty() {
+        writeln!(out, "Nothing has been deleted or pruned yet")?;
+        return Ok(());
+    }
+    let user_width = records.iter().map(|r| r.user.len()).max().unwrap_or(0);
+    for record in records.iter().rev() {
+        let entry = &record.entry;
+        writeln!(
+            out,
+            "{} UTC  {:<user_width$}  {:<10}  {:<13}  {:<14}  {:<20}  {}",
+            format_date(record.time),
+            record.user,
+            entry.removal.key(),
+            trigger(entry),
+            entry.file_type.as_ref().map_or("-", |t| t.key()),
+            status(entry),
+            entry.target,
+            user_width = user_width
+        )?;
+    }
+    Ok(())
+}
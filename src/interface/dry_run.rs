@@ -0,0 +1,29 @@
+use crate::domain::entities::SizeMode;
+use crate::domain::ports::DryRunLog;
+use crate::utils::format_amount;
+use std::io::{self, Write};
+
+/// Prints what a dry run would have removed, once the interface closes,
+/// measured in `mode`.
+pub fn print(log: &DryRunLog, mode: SizeMode, mut out: impl Write) -> io::Result<()> {
+    let removals = log.entries();
+    if removals.is_empty() {
+        writeln!(out, "Dry run: nothing would have been removed")?;
+        return Ok(());
+    }
+    writeln!(
+        out,
+        "Dry run: {} removals would have freed {}",
+        removals.len(),
+        format_amount(log.total(mode), mode)
+    )?;
+    for removal in removals {
+        writeln!(
+            out,
+            "  {:>10}  {}",
+            format_amount(removal.size(mode), mode),
+            removal.target
+        )?;
+    }
+    Ok(())
+}
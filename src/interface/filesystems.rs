@@ -0,0 +1,46 @@
+use crate::domain::entities::Filesystem;
+use crate::utils::{format_size, usage_bar};
+use std::io::{self, Write};
+
+/// Prints the mounted filesystems as a table, for `diskdoc df`.
+pub fn print(filesystems: &[Filesystem], mut out: impl Write) -> io::Result<()> {
+    let mount_width = filesystems
+        .iter()
+        .map(|fs| fs.mount_point.display().to_string().len())
+        .chain(["Mounted on".len()])
+        .max()
+        .unwrap_or(0);
+    writeln!(
+        out,
+        "{:<mount_width$}  {:<8}  {:>10}  {:>10}  {:>10}  {:<16}  {:>6}  Options",
+        "Mounted on",
+        "Type",
+        "Size",
+        "Used",
+        "Free",
+        "Use%",
+        "IUse%",
+        mount_width = mount_width
+    )?;
+    for fs in filesystems {
+        let inodes = fs
+            .inode_usage()
+            .map(|ratio| format!("{:.0}%", ratio * 100.0))
+            .unwrap_or_else(|| "-".to_string());
+        writeln!(
+            out,
+            "{:<mount_width$}  {:<8}  {:>10}  {:>10}  {:>10}  {} {:>4.0}%  {:>6}  {}",
+            fs.mount_point.display(),
+            fs.fs_type,
+            format_size(fs.total),
+            format_size(fs.used()),
+            format_size(fs.available),
+            usage_bar(fs.usage(), 10),
+            fs.usage() * 100.0,
+            inodes,
+            fs.options.join(","),
+            mount_width = mount_width
+        )?;
+    }
+    Ok(())
+}
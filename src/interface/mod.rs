@@ -1,2 +1,6 @@
+pub mod audit;
+pub mod dry_run;
+pub mod filesystems;
+pub mod report;
+pub mod trends;
 pub mod tui;
-// pub mod cli; // Future
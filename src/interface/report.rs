@@ -0,0 +1,331 @@
+use crate::application::app::App;
+use crate::domain::entities::{
+    FileStats, Recommendation, ScanError, ScanErrorKind, ScanProgress, SizeMode, SortOrder,
+};
+use crate::domain::ports::{DryRunLog, ScanEvent};
+use crate::domain::tree::NodeId;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Bumped whenever a field is removed or changes meaning.
+pub const REPORT_VERSION: u32 = 1;
+
+/// Machine-readable summary of a finished scan, printed by `--json`.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub version: u32,
+    pub root: String,
+    pub size_mode: &'static str,
+    /// Order of the entry lists.
+    pub sort: SortReport,
+    /// Unix timestamp, in seconds, of when the report was built.
+    pub generated_at: u64,
+    pub summary: Summary,
+    /// Largest direct children of the root, in `sort` order.
+    pub top_entries: Vec<EntryReport>,
+    /// Largest files anywhere below the root, in `sort` order.
+    pub largest_files: Vec<EntryReport>,
+    pub file_types: Vec<FileTypeTotal>,
+    pub recommendations: Vec<RecommendationReport>,
+    pub errors: Vec<ErrorReport>,
+    /// What the cleanups would remove; only present with `--dry-run`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<DryRunReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SortReport {
+    pub key: &'static str,
+    pub order: &'static str,
+    pub dirs_first: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub size: u64,
+    pub apparent_size: u64,
+    pub allocated_size: u64,
+    pub entries: usize,
+    pub files: usize,
+    pub directories: usize,
+    pub excluded: usize,
+    pub errors: usize,
+    pub incomplete: bool,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EntryReport {
+    pub path: String,
+    pub size: u64,
+    pub apparent_size: u64,
+    pub allocated_size: u64,
+    pub is_dir: bool,
+    /// Entries below a directory; absent for files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entries: Option<usize>,
+    pub file_type: &'static str,
+    pub modified: u64,
+    pub incomplete: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileTypeTotal {
+    pub file_type: &'static str,
+    pub size: u64,
+    pub files: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecommendationReport {
+    pub category: &'static str,
+    pub description: String,
+    pub size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action_command: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DryRunReport {
+    /// What the removals would free, in `size_mode`.
+    pub size: u64,
+    pub removals: Vec<PlannedRemovalReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlannedRemovalReport {
+    pub target: String,
+    pub size: u64,
+    pub apparent_size: u64,
+    pub allocated_size: u64,
+}
+
+impl DryRunReport {
+    pub fn new(log: &DryRunLog, mode: SizeMode) -> Self {
+        DryRunReport {
+            size: log.total(mode),
+            removals: log
+                .entries()
+                .into_iter()
+                .map(|removal| PlannedRemovalReport {
+                    size: removal.size(mode),
+                    apparent_size: removal.apparent_size,
+                    allocated_size: removal.allocated_size,
+                    target: removal.target,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errno: Option<i32>,
+    pub message: String,
+}
+
+/// One line of `--ndjson` output, tagged with a `type` field.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamRecord<'a> {
+    Entry {
+        path: String,
+        apparent_size: u64,
+        allocated_size: u64,
+        is_dir: bool,
+    },
+    Progress {
+        bytes_seen: u64,
+        entries_seen: usize,
+        errors: usize,
+        excluded: usize,
+        elapsed_ms: u64,
+    },
+    Error(ErrorReport),
+    Report(&'a Report),
+}
+
+impl Report {
+    /// Builds the report from a finished scan, refreshing the app's
+    /// recommendations the same way the dashboard does.
+    pub fn build(app: &mut App, top_n: usize) -> Report {
+        app.scan_dashboard();
+        let tree = &app.tree;
+        let mode = app.size_mode;
+        let root = tree.node(tree.root());
+
+        let mut files = 0;
+        let mut file_types: BTreeMap<&'static str, (u64, usize)> = BTreeMap::new();
+        let mut file_ids = Vec::new();
+        for id in tree.iter() {
+            let node = tree.node(id);
+            if node.is_dir {
+                continue;
+            }
+            files += 1;
+            file_ids.push(id);
+            let total = file_types.entry(node.file_type.key()).or_default();
+            total.0 += node.counted_size(mode);
+            total.1 += 1;
+        }
+
+        // Pick the largest entries, then list them in the requested order.
+        let mut top_entries = tree.children(tree.root()).to_vec();
+        sort_by_size(app, &mut top_entries);
+        top_entries.truncate(top_n);
+        tree.sort(&mut top_entries, app.sort, mode);
+        sort_by_size(app, &mut file_ids);
+        file_ids.truncate(top_n);
+        tree.sort(&mut file_ids, app.sort, mode);
+
+        let mut file_types: Vec<FileTypeTotal> = file_types
+            .into_iter()
+            .map(|(file_type, (size, files))| FileTypeTotal {
+                file_type,
+                size,
+                files,
+            })
+            .collect();
+        file_types.sort_by_key(|total| std::cmp::Reverse(total.size));
+
+        Report {
+            version: REPORT_VERSION,
+            root: display(tree.root_path()),
+            size_mode: size_mode_name(mode),
+            sort: SortReport {
+                key: app.sort.key.key(),
+                order: match app.sort.order {
+                    SortOrder::Desc => "desc",
+                    SortOrder::Asc => "asc",
+                },
+                dirs_first: app.sort.dirs_first,
+            },
+            generated_at: unix_seconds(SystemTime::now()),
+            summary: Summary {
+                size: root.size(mode),
+                apparent_size: root.apparent_size,
+                allocated_size: root.allocated_size,
+                entries: tree.len(),
+                files,
+                directories: tree.len() - files,
+                excluded: app.progress.excluded,
+                errors: app.errors.len(),
+                incomplete: root.incomplete,
+                elapsed_ms: app.progress.elapsed.as_millis() as u64,
+            },
+            top_entries: top_entries.iter().map(|&id| entry(app, id)).collect(),
+            largest_files: file_ids.iter().map(|&id| entry(app, id)).collect(),
+            file_types,
+            recommendations: app.recommendations.iter().map(recommendation).collect(),
+            errors: app.errors.iter().map(ErrorReport::from).collect(),
+            dry_run: app.dry_run.as_ref().map(|log| DryRunReport::new(log, mode)),
+        }
+    }
+}
+
+impl StreamRecord<'_> {
+    /// Maps a scan event to its stream record. Completion is reported by
+    /// the final `report` record instead.
+    pub fn from_event(event: &ScanEvent) -> Option<StreamRecord<'static>> {
+        match event {
+            ScanEvent::NewEntry(stats) => Some(stream_entry(stats)),
+            ScanEvent::Progress(progress) => Some(stream_progress(progress)),
+            ScanEvent::Error(error) => Some(StreamRecord::Error(error.into())),
+            ScanEvent::Complete => None,
+        }
+    }
+}
+
+impl From<&ScanError> for ErrorReport {
+    fn from(error: &ScanError) -> Self {
+        ErrorReport {
+            path: error.path.as_deref().map(display),
+            kind: error_kind_name(error.kind),
+            errno: error.errno,
+            message: error.message.clone(),
+        }
+    }
+}
+
+fn stream_entry(stats: &FileStats) -> StreamRecord<'static> {
+    StreamRecord::Entry {
+        path: display(&stats.path),
+        apparent_size: stats.size,
+        allocated_size: stats.allocated_size,
+        is_dir: stats.is_dir,
+    }
+}
+
+fn stream_progress(progress: &ScanProgress) -> StreamRecord<'static> {
+    StreamRecord::Progress {
+        bytes_seen: progress.bytes_seen,
+        entries_seen: progress.entries_seen,
+        errors: progress.errors,
+        excluded: progress.excluded,
+        elapsed_ms: progress.elapsed.as_millis() as u64,
+    }
+}
+
+fn entry(app: &App, id: NodeId) -> EntryReport {
+    let node = app.tree.node(id);
+    EntryReport {
+        path: display(&app.tree.path(id)),
+        size: node.size(app.size_mode),
+        apparent_size: node.apparent_size,
+        allocated_size: node.allocated_size,
+        is_dir: node.is_dir,
+        entries: node.is_dir.then_some(node.descendants),
+        file_type: node.file_type.key(),
+        modified: unix_seconds(node.modified),
+        incomplete: node.incomplete,
+    }
+}
+
+fn recommendation(rec: &Recommendation) -> RecommendationReport {
+    RecommendationReport {
+        category: rec.category.key(),
+        description: rec.description.clone(),
+        size: rec.size,
+        path: rec.path.as_deref().map(display),
+        action_command: rec.action_command.clone(),
+    }
+}
+
+fn sort_by_size(app: &App, ids: &mut [NodeId]) {
+    ids.sort_by_key(|&id| std::cmp::Reverse(app.tree.node(id).size(app.size_mode)));
+}
+
+fn size_mode_name(mode: SizeMode) -> &'static str {
+    match mode {
+        SizeMode::Disk => "disk",
+        SizeMode::Apparent => "apparent",
+        SizeMode::Count => "count",
+    }
+}
+
+fn error_kind_name(kind: ScanErrorKind) -> &'static str {
+    match kind {
+        ScanErrorKind::PermissionDenied => "permission_denied",
+        ScanErrorKind::Vanished => "vanished",
+        ScanErrorKind::Io => "io",
+        ScanErrorKind::Other => "other",
+    }
+}
+
+fn display(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
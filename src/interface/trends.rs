@@ -0,0 +1,144 @@
+use crate::domain::history::{self, HistoryRecord};
+use crate::utils::{format_date, format_delta, format_duration, format_size, sparkline};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Sizes of one named entry across a series of history records.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeriesRow {
+    pub name: String,
+    /// One value per record, 0 where the entry was absent.
+    pub values: Vec<u64>,
+}
+
+impl SeriesRow {
+    pub fn first(&self) -> u64 {
+        self.values.first().copied().unwrap_or(0)
+    }
+
+    pub fn last(&self) -> u64 {
+        self.values.last().copied().unwrap_or(0)
+    }
+
+    pub fn delta(&self) -> i64 {
+        self.last() as i64 - self.first() as i64
+    }
+}
+
+/// Top-level directories, largest growth first.
+pub fn top_level_rows(records: &[HistoryRecord]) -> Vec<SeriesRow> {
+    rows(records, |record| &record.top_level)
+}
+
+/// File type totals, largest growth first.
+pub fn file_type_rows(records: &[HistoryRecord]) -> Vec<SeriesRow> {
+    rows(records, |record| &record.file_types)
+}
+
+fn rows(
+    records: &[HistoryRecord],
+    sizes: impl Fn(&HistoryRecord) -> &BTreeMap<String, u64>,
+) -> Vec<SeriesRow> {
+    let mut names: Vec<&String> = records.iter().flat_map(|r| sizes(r).keys()).collect();
+    names.sort();
+    names.dedup();
+
+    let mut rows: Vec<SeriesRow> = names
+        .into_iter()
+        .map(|name| SeriesRow {
+            name: name.clone(),
+            values: records
+                .iter()
+                .map(|r| sizes(r).get(name).copied().unwrap_or(0))
+                .collect(),
+        })
+        .collect();
+    rows.sort_by_key(|row| std::cmp::Reverse(row.delta()));
+    rows
+}
+
+/// One-line summary of the growth rate and fill-up projection.
+pub fn projection(records: &[HistoryRecord]) -> String {
+    let Some(trend) = history::trend(records) else {
+        return "Not enough history for a projection yet".to_string();
+    };
+    let rate = format!("{}/day", format_delta(trend.growth_per_day as i64));
+    match trend.time_to_full {
+        Some(time) => format!(
+            "Growing {}; the filesystem fills up in about {} at this rate",
+            rate,
+            format_days(time.as_secs())
+        ),
+        None if trend.growth_per_day > 0.0 => format!("Growing {}", rate),
+        None => format!("Not growing ({})", rate),
+    }
+}
+
+/// Prints the history of `root` as text, for `diskdoc history`.
+pub fn print(root: &Path, records: &[HistoryRecord], mut out: impl Write) -> io::Result<()> {
+    let (Some(first), Some(last)) = (records.first(), records.last()) else {
+        writeln!(
+            out,
+            "No history for {}; scan it with --history to start recording",
+            root.display()
+        )?;
+        return Ok(());
+    };
+    writeln!(
+        out,
+        "History of {} ({} scans, {} .. {} UTC)",
+        root.display(),
+        records.len(),
+        format_date(first.time),
+        format_date(last.time)
+    )?;
+    let totals: Vec<u64> = records.iter().map(|r| r.size).collect();
+    writeln!(
+        out,
+        "Total  {}  {} -> {} ({})",
+        sparkline(&totals),
+        format_size(first.size),
+        format_size(last.size),
+        format_delta(last.size as i64 - first.size as i64)
+    )?;
+    if let Some(fs) = last.filesystem {
+        writeln!(
+            out,
+            "Filesystem: {} free of {}",
+            format_size(fs.available),
+            format_size(fs.total)
+        )?;
+    }
+    writeln!(out, "{}", projection(records))?;
+
+    for (title, rows) in [
+        ("Top-level entries", top_level_rows(records)),
+        ("File types", file_type_rows(records)),
+    ] {
+        writeln!(out)?;
+        writeln!(out, "{}", title)?;
+        let width = rows.iter().map(|row| row.name.len()).max().unwrap_or(0);
+        for row in rows {
+            writeln!(
+                out,
+                "  {:<width$}  {}  {} ({})",
+                row.name,
+                sparkline(&row.values),
+                format_size(row.last()),
+                format_delta(row.delta()),
+                width = width
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn format_days(secs: u64) -> String {
+    let days = secs / 86_400;
+    if days >= 1 {
+        format!("{} days", days)
+    } else {
+        format_duration(std::time::Duration::from_secs(secs))
+    }
+}
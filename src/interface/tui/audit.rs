@@ -0,0 +1,66 @@
+use crate::application::app::App;
+use crate::interface::audit::{status, trigger};
+use crate::utils::format_date;
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+pub fn draw_audit(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    if app.audit_records.is_empty() {
+        let text = Paragraph::new(" Nothing has been deleted or pruned yet.")
+            .block(Block::default().title(" Audit log ").borders(Borders::ALL));
+        f.render_widget(text, area);
+        return;
+    }
+
+    let user_width = app
+        .audit_records
+        .iter()
+        .map(|r| r.user.len())
+        .max()
+        .unwrap_or(0);
+    let items: Vec<ListItem> = app
+        .audit_records
+        .iter()
+        .map(|record| {
+            let entry = &record.entry;
+            let status_style = if entry.outcome.is_ok() {
+                Style::default().fg(Color::Green)
+            } else {
+                Style::default().fg(Color::Red)
+            };
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{}  ", format_date(record.time))),
+                Span::styled(
+                    format!("{:<width$}  ", record.user, width = user_width),
+                    Style::default().fg(Color::Cyan),
+                ),
+                Span::raw(format!(
+                    "{:<10}  {:<13}  {:<14}  ",
+                    entry.removal.key(),
+                    trigger(entry),
+                    entry.file_type.as_ref().map_or("-", |t| t.key())
+                )),
+                Span::styled(format!("{:<20}  ", status(entry)), status_style),
+                Span::raw(entry.target.clone()),
+            ]))
+        })
+        .collect();
+    let title = format!(
+        " Audit log: {} records, newest first (UTC) | [j/k] Select | [Esc] Back ",
+        app.audit_records.len()
+    );
+    let list = List::new(items)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .fg(Color::Green),
+        );
+    let mut state = ListState::default();
+    state.select(Some(app.audit_selection));
+    f.render_stateful_widget(list, area, &mut state);
+}
@@ -1,6 +1,6 @@
 use crate::application::app::App;
-use crate::domain::entities::RecommendationCategory;
-use crate::utils::format_size;
+use crate::domain::entities::{RecommendationCategory, SizeMode};
+use crate::utils::format_amount;
 use ratatui::{
     layout::{Constraint, Direction, Layout},
     style::{Color, Modifier, Style},
@@ -22,15 +22,21 @@ pub fn draw_dashboard(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
         .split(area);
 
     // Title / High-level stats
-    let total_reclaimable: u64 = app.recommendations.iter().map(|r| r.size).sum();
+    let total_reclaimable: u64 = app
+        .recommendations
+        .iter()
+        .filter(|r| r.category != RecommendationCategory::SmallFiles)
+        .map(|r| r.size)
+        .sum();
+    let reclaimable_label = match app.size_mode {
+        SizeMode::Count => " Potential Reclaimable Entries: ",
+        SizeMode::Disk | SizeMode::Apparent => " Potential Reclaimable Space: ",
+    };
 
     let stats_text = vec![Line::from(vec![
+        Span::styled(reclaimable_label, Style::default().fg(Color::Yellow)),
         Span::styled(
-            " Potential Reclaimable Space: ",
-            Style::default().fg(Color::Yellow),
-        ),
-        Span::styled(
-            format_size(total_reclaimable),
+            format_amount(total_reclaimable, app.size_mode),
             Style::default().add_modifier(Modifier::BOLD).fg(Color::Red),
         ),
     ])];
@@ -49,6 +55,7 @@ pub fn draw_dashboard(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
                 RecommendationCategory::Log => ("📝", Color::Red),
                 RecommendationCategory::Cache => ("⚡", Color::Yellow),
                 RecommendationCategory::Trash => ("🗑️", Color::Gray),
+                RecommendationCategory::SmallFiles => ("🗂️", Color::Magenta),
                 RecommendationCategory::Other => ("📦", Color::White),
             };
 
@@ -60,7 +67,7 @@ pub fn draw_dashboard(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
                 ),
                 Span::raw(" | "),
                 Span::styled(
-                    format!("{:<10} ", format_size(r.size)),
+                    format!("{:<10} ", format_amount(r.size, app.size_mode)),
                     Style::default().fg(Color::Green),
                 ),
                 Span::raw(" | "),
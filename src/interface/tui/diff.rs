@@ -0,0 +1,125 @@
+use crate::application::app::App;
+use crate::domain::diff::DiffKind;
+use crate::domain::entities::SortOrder;
+use crate::utils::{format_amount, format_amount_delta};
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+pub fn draw_diff(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let Some(diff) = &app.diff else {
+        return;
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+        .split(area);
+
+    // Totals of the directory being shown
+    let total = diff.entry(&app.diff_path, app.size_mode);
+    let stats_text = vec![Line::from(vec![
+        Span::raw(format!(
+            " {} -> {} ",
+            format_amount(total.old_size.unwrap_or(0), app.size_mode),
+            format_amount(total.new_size.unwrap_or(0), app.size_mode)
+        )),
+        Span::styled(
+            format_amount_delta(total.delta(), app.size_mode),
+            delta_style(total.delta()).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!(" ({})", app.size_mode.label()),
+            Style::default().fg(Color::DarkGray),
+        ),
+    ])];
+    let title = format!(
+        " Changes: {} -> {} ",
+        diff.old().root_path().display(),
+        diff.new_tree().root_path().display()
+    );
+    let stats =
+        Paragraph::new(stats_text).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(stats, chunks[0]);
+
+    let entries = app.diff_entries();
+    let items: Vec<ListItem> = entries
+        .iter()
+        .map(|entry| {
+            let name = entry.path.file_name().unwrap_or_default().to_string_lossy();
+            let name = if entry.is_dir {
+                Span::styled(
+                    format!("{}/", name),
+                    Style::default()
+                        .add_modifier(Modifier::BOLD)
+                        .fg(Color::Blue),
+                )
+            } else {
+                Span::raw(name.to_string())
+            };
+            let sizes = match entry.kind() {
+                DiffKind::Added => format!(
+                    " (new, {})",
+                    format_amount(entry.new_size.unwrap_or(0), app.size_mode)
+                ),
+                DiffKind::Removed => {
+                    format!(
+                        " (was {})",
+                        format_amount(entry.old_size.unwrap_or(0), app.size_mode)
+                    )
+                }
+                _ => format!(
+                    " ({} -> {})",
+                    format_amount(entry.old_size.unwrap_or(0), app.size_mode),
+                    format_amount(entry.new_size.unwrap_or(0), app.size_mode)
+                ),
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:<12} ", format_amount_delta(entry.delta(), app.size_mode)),
+                    delta_style(entry.delta()),
+                ),
+                Span::styled(
+                    format!("{:<8} ", entry.kind().label()),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                name,
+                Span::styled(sizes, Style::default().fg(Color::DarkGray)),
+            ]))
+        })
+        .collect();
+
+    let order = match app.sort.order {
+        SortOrder::Desc => "largest growth first",
+        SortOrder::Asc => "largest shrink first",
+    };
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .title(format!(" {} changed ({}) ", entries.len(), order))
+                .borders(Borders::ALL),
+        )
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .fg(Color::Green),
+        );
+
+    let mut state = ListState::default();
+    if !entries.is_empty() {
+        state.select(Some(app.diff_selection));
+    }
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+/// Growth is shown in red since it eats disk space, shrinkage in green.
+fn delta_style(delta: i64) -> Style {
+    match delta {
+        d if d > 0 => Style::default().fg(Color::Red),
+        d if d < 0 => Style::default().fg(Color::Green),
+        _ => Style::default(),
+    }
+}
@@ -0,0 +1,91 @@
+use crate::application::app::App;
+use crate::interface::tui::widgets::disk_usage_bar;
+use crate::utils::format_size;
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+pub fn draw_filesystems(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(4)].as_ref())
+        .split(area);
+
+    let mount_width = app
+        .filesystems
+        .iter()
+        .map(|fs| fs.mount_point.display().to_string().len())
+        .max()
+        .unwrap_or(0)
+        .min(40);
+    let items: Vec<ListItem> = app
+        .filesystems
+        .iter()
+        .enumerate()
+        .map(|(i, fs)| {
+            let style = if i == app.filesystem_selection {
+                Style::default()
+                    .bg(Color::Blue)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let inodes = fs
+                .inode_usage()
+                .map(|ratio| format!("{:>3.0}%", ratio * 100.0))
+                .unwrap_or_else(|| "   -".to_string());
+            ListItem::new(Line::from(vec![
+                Span::raw(format!(
+                    "{:<mount_width$} ",
+                    fs.mount_point.display(),
+                    mount_width = mount_width
+                )),
+                disk_usage_bar(fs.usage(), 20),
+                Span::raw(format!(
+                    " {:>3.0}%  {:>10} of {:<10}  inodes {}  {}",
+                    fs.usage() * 100.0,
+                    format_size(fs.available),
+                    format_size(fs.total),
+                    inodes,
+                    fs.fs_type
+                )),
+            ]))
+            .style(style)
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .title(" Filesystems - [Enter] Scan ")
+            .borders(Borders::ALL),
+    );
+    let mut state = ListState::default();
+    state.select(Some(app.filesystem_selection));
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    // Details of the selected filesystem
+    let details = match app.filesystems.get(app.filesystem_selection) {
+        Some(fs) => vec![
+            Line::from(format!(
+                " {} on {} ({}){}",
+                fs.device,
+                fs.mount_point.display(),
+                fs.fs_type,
+                if fs.is_read_only() { ", read-only" } else { "" }
+            )),
+            Line::from(Span::styled(
+                format!(" Options: {}", fs.options.join(",")),
+                Style::default().fg(Color::DarkGray),
+            )),
+        ],
+        None => vec![Line::from(" No mounted filesystems found")],
+    };
+    f.render_widget(
+        Paragraph::new(details).block(Block::default().borders(Borders::ALL)),
+        chunks[1],
+    );
+}
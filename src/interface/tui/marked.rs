@@ -0,0 +1,137 @@
+use super::{centered_rect, limit_line, removal_line};
+use crate::application::app::App;
+use crate::utils::{format_amount, format_size};
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+pub fn draw_marked(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let results_height = if app.deletion_results.is_empty() {
+        0
+    } else {
+        (app.deletion_results.len() as u16 + 2).min(area.height / 2)
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(results_height)].as_ref())
+        .split(area);
+
+    let items: Vec<ListItem> = app
+        .marked
+        .iter()
+        .map(|path| {
+            let size = match app.tree.find(path) {
+                Some(id) => format_amount(app.tree.node(id).size(app.size_mode), app.size_mode),
+                None => "-".to_string(),
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:<10} ", size), Style::default().fg(Color::Yellow)),
+                Span::raw(path.display().to_string()),
+            ]))
+        })
+        .collect();
+    let title = format!(
+        " Marked: {} items, {} | [d] Delete all | [Space] Unmark | [Esc] Back ",
+        app.marked.len(),
+        format_amount(app.marked_size(), app.size_mode)
+    );
+    let list = List::new(items)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .fg(Color::Green),
+        );
+    let mut state = ListState::default();
+    state.select(Some(app.marked_selection));
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    if !app.deletion_results.is_empty() {
+        let freed: u64 = app
+            .deletion_results
+            .iter()
+            .filter_map(|result| result.outcome.as_ref().ok())
+            .sum();
+        let failed = app
+            .deletion_results
+            .iter()
+            .filter(|result| result.outcome.is_err())
+            .count();
+        let items: Vec<ListItem> = app
+            .deletion_results
+            .iter()
+            .map(|result| {
+                let (status, style) = match &result.outcome {
+                    Ok(bytes) => (
+                        format!("freed {}", format_size(*bytes)),
+                        Style::default().fg(Color::Green),
+                    ),
+                    Err(e) => (format!("failed: {}", e), Style::default().fg(Color::Red)),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("{}  ", result.path.display())),
+                    Span::styled(status, style),
+                ]))
+            })
+            .collect();
+        let title = format!(
+            " Deleted {} of {}, freed {} ",
+            app.deletion_results.len() - failed,
+            app.deletion_results.len(),
+            format_size(freed)
+        );
+        f.render_widget(
+            List::new(items).block(Block::default().title(title).borders(Borders::ALL)),
+            chunks[1],
+        );
+    }
+}
+
+pub fn draw_batch_delete_popup(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let block = Block::default()
+        .title(" Confirm Deletion ")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Red).add_modifier(Modifier::BOLD));
+
+    let area = centered_rect(60, 25, area);
+
+    let text = vec![
+        Line::from(format!(
+            "Are you sure you want to delete {} marked items?",
+            app.marked_roots().len()
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            format_amount(app.marked_size(), app.size_mode),
+            Style::default().fg(Color::Yellow),
+        )),
+        removal_line(app, " every marked item."),
+        limit_line(app),
+        Line::from(vec![
+            Span::raw("Press "),
+            Span::styled("[y]", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" or "),
+            Span::styled("[Enter]", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to Confirm"),
+        ]),
+        Line::from(vec![
+            Span::raw("Press "),
+            Span::styled("[n]", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" or "),
+            Span::styled("[Esc]", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to Cancel"),
+        ]),
+    ];
+
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .alignment(ratatui::layout::Alignment::Center)
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
@@ -1,34 +1,64 @@
 use crate::application::app::{App, AppMode};
-use crate::domain::entities::FileType;
-use crate::utils::format_size;
+use crate::domain::entities::{Column, Removal};
+use crate::utils::{
+    format_amount, format_count, format_date, format_duration, format_size, usage_bar,
+};
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Clear, Gauge, List, ListItem, Paragraph, Wrap},
     Frame,
 };
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
+pub mod audit;
 pub mod dashboard;
+pub mod diff;
+pub mod filesystems;
+pub mod marked;
 pub mod theme;
+pub mod treemap;
+pub mod trends;
 pub mod widgets;
 
 pub fn draw(f: &mut Frame, app: &App) {
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .margin(1)
-        .constraints(
-            [
-                Constraint::Length(3), // Header
-                Constraint::Min(0),    // Content
-                Constraint::Length(3), // Footer/Status
-            ]
-            .as_ref(),
-        )
-        .split(f.size());
+    let chunks = screen_chunks(f.size());
 
     // 1. Header
-    let title = format!(" DiskDoctor - {} ", app.current_path.display());
+    let snapshot = app
+        .snapshot
+        .as_ref()
+        .map(|meta| {
+            let age = SystemTime::now()
+                .duration_since(meta.created)
+                .unwrap_or_default();
+            format!(
+                "(snapshot from {}, {} ago) ",
+                meta.host,
+                format_duration(age)
+            )
+        })
+        .unwrap_or_default();
+    let dry_run = app
+        .dry_run
+        .as_ref()
+        .map(|log| {
+            format!(
+                "[DRY RUN: {} items, {} would be freed] ",
+                log.entries().len(),
+                format_amount(log.total(app.size_mode), app.size_mode)
+            )
+        })
+        .unwrap_or_default();
+    let title = format!(
+        " DiskDoctor - {} [sort: {}] {}{}",
+        app.current_path.display(),
+        app.sort.label(),
+        snapshot,
+        dry_run
+    );
     let header = Block::default()
         .title(title)
         .borders(Borders::ALL)
@@ -40,7 +70,18 @@ pub fn draw(f: &mut Frame, app: &App) {
         AppMode::Scanning => draw_scanning(f, app, chunks[1]),
         AppMode::Browsing => draw_browsing(f, app, chunks[1]),
         AppMode::Dashboard => dashboard::draw_dashboard(f, app, chunks[1]),
+        AppMode::Treemap => treemap::draw_treemap(f, app, chunks[1]),
         AppMode::About => draw_about(f, chunks[1]),
+        AppMode::Errors => draw_errors(f, app, chunks[1]),
+        AppMode::Diff => diff::draw_diff(f, app, chunks[1]),
+        AppMode::Trends => trends::draw_trends(f, app, chunks[1]),
+        AppMode::Filesystems => filesystems::draw_filesystems(f, app, chunks[1]),
+        AppMode::Marked => marked::draw_marked(f, app, chunks[1]),
+        AppMode::Audit => audit::draw_audit(f, app, chunks[1]),
+        AppMode::BatchDeleteConfirmation => {
+            marked::draw_marked(f, app, chunks[1]);
+            marked::draw_batch_delete_popup(f, app, f.size());
+        }
         AppMode::DeleteConfirmation => {
             draw_browsing(f, app, chunks[1]); // Draw background
             draw_delete_popup(f, app, f.size()); // Draw popup over full screen
@@ -52,30 +93,139 @@ pub fn draw(f: &mut Frame, app: &App) {
     }
 
     // 3. Footer
-    let status_text = format!(
-        " [1] Dashboard | [2] Files | Total: {} | Files: {} | [s] Sort | [d] Delete | [q] Quit ",
-        format_size(app.total_size),
-        app.scanned_count
-    );
+    let excluded = if app.progress.excluded > 0 {
+        format!(" | {} entries excluded", app.progress.excluded)
+    } else {
+        String::new()
+    };
+    let errors = if app.errors.is_empty() {
+        String::new()
+    } else {
+        format!(" | Errors: {} [e]", app.errors.len())
+    };
+    let marked = if app.marked.is_empty() {
+        String::new()
+    } else {
+        format!(
+            " | Marked: {} ({}) [m]",
+            app.marked.len(),
+            format_amount(app.marked_size(), app.size_mode)
+        )
+    };
+    let notice = app
+        .notice
+        .as_ref()
+        .map(|notice| format!(" | {}", notice))
+        .unwrap_or_default();
+    // Imports and snapshots are browse-only.
+    let actions = if app.read_only_reason().is_some() {
+        " Read-only |"
+    } else if app.cleaner.removal() == Removal::Quarantine {
+        " [r] Rescan | [d] Delete | [u] Undo |"
+    } else {
+        " [r] Rescan | [d] Delete |"
+    };
+    let status_text = if app.mode == AppMode::Diff {
+        format!(
+            " Diff ({}) | [Enter] Open | [Backspace] Up | [s] Sort by growth/shrink | [a] Size | [q] Quit ",
+            app.size_mode.label()
+        )
+    } else if app.mode == AppMode::Filesystems {
+        " [Enter] Scan mount | [j/k] Select | [Esc] Back to results | [q] Quit ".to_string()
+    } else {
+        format!(
+        " [1] Dashboard | [2] Files | [3] Treemap | Total: {} ({}) | Files: {}{}{}{}{}{} | [s/o/g] Sort | [a] Size | [i] Count |{} [q] Quit ",
+        format_amount(app.total_size(), app.size_mode),
+        app.size_mode.label(),
+        app.tree.len(),
+        excluded,
+        errors,
+        marked,
+        notice,
+        if app.is_scanning { " | Scanning..." } else { "" },
+        actions
+    )
+    };
     let footer = Paragraph::new(status_text).block(Block::default().borders(Borders::ALL));
     f.render_widget(footer, chunks[2]);
 }
 
+/// Header, content and footer areas of the screen.
+fn screen_chunks(size: Rect) -> Rc<[Rect]> {
+    Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints(
+            [
+                Constraint::Length(3), // Header
+                Constraint::Min(0),    // Content
+                Constraint::Length(3), // Footer/Status
+            ]
+            .as_ref(),
+        )
+        .split(size)
+}
+
+/// Area the current view is drawn in, for a terminal of `size`.
+pub fn content_area(size: Rect) -> Rect {
+    screen_chunks(size)[1]
+}
+
 fn draw_scanning(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
         .split(area);
 
+    let progress = &app.progress;
+    let (percent, label) = match progress.percent() {
+        Some(percent) => {
+            let eta = progress
+                .eta()
+                .map(format_duration)
+                .unwrap_or_else(|| "-".to_string());
+            (percent, format!("{}% (ETA {})", percent, eta))
+        }
+        None => (0, format!("{} scanned", format_size(progress.bytes_seen))),
+    };
+
+    let title = if app.is_paused() {
+        "Paused - [p] Resume | [x] Stop and browse"
+    } else {
+        "Scanning... [p] Pause | [x] Stop and browse"
+    };
     let gauge = Gauge::default()
-        .block(Block::default().title("Scanning...").borders(Borders::ALL))
+        .block(Block::default().title(title).borders(Borders::ALL))
         .gauge_style(Style::default().fg(Color::Yellow))
-        .percent(0); // Indeterminate or based on expected count?
+        .percent(percent)
+        .label(label);
 
     f.render_widget(gauge, chunks[0]);
 
-    let info = Paragraph::new(format!("Found {} files...", app.scanned_count))
-        .block(Block::default().borders(Borders::NONE));
+    let current_dir = progress
+        .current_dir
+        .as_ref()
+        .map(|p| p.display().to_string())
+        .unwrap_or_default();
+    let text = vec![
+        Line::from(format!(
+            "Found {} files ({})",
+            app.tree.len(),
+            format_size(progress.bytes_seen)
+        )),
+        Line::from(format!(
+            "Elapsed: {} | Throughput: {}/s | Errors: {} | Excluded: {}",
+            format_duration(progress.elapsed),
+            format_size(progress.throughput() as u64),
+            progress.errors,
+            progress.excluded
+        )),
+        Line::from(Span::styled(
+            current_dir,
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    let info = Paragraph::new(text).block(Block::default().borders(Borders::NONE));
     f.render_widget(info, chunks[1]);
 }
 
@@ -84,28 +234,21 @@ fn draw_browsing(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     // Or a simple list
 
     // List only files in current directory
-    let current_files = app.get_current_files();
+    let current_children = app.current_children();
+    let parent_size = app.tree.node(app.current_dir).size(app.size_mode);
 
-    let items: Vec<ListItem> = current_files
+    let items: Vec<ListItem> = current_children
         .iter()
         .take(100)
-        .map(|f| {
+        .map(|&id| {
+            let f = app.tree.stats(id);
+            let incomplete = app.tree.node(id).incomplete;
             let name = f.path.file_name().unwrap_or_default().to_string_lossy();
 
-            let style = if f.is_dir {
-                Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Blue)
-            } else {
-                match f.file_type {
-                    FileType::Log => Style::default().fg(Color::Red),
-                    FileType::Cache => Style::default().fg(Color::Yellow),
-                    FileType::NpmCache | FileType::ComposerCache | FileType::AptCache => {
-                        Style::default().fg(Color::Yellow)
-                    }
-                    FileType::Docker => Style::default().fg(Color::Cyan),
-                    FileType::Normal => Style::default(),
-                }
+            let style = match theme::entry_color(f.is_dir, &f.file_type) {
+                Some(color) if f.is_dir => Style::default().add_modifier(Modifier::BOLD).fg(color),
+                Some(color) => Style::default().fg(color),
+                None => Style::default(),
             };
 
             let name_styled = if f.is_dir {
@@ -114,13 +257,75 @@ fn draw_browsing(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
                 Span::styled(name.to_string(), style)
             };
 
-            let content = Line::from(vec![
+            let mark = if app.marked.contains(&f.path) {
+                "* "
+            } else {
+                "  "
+            };
+            let mut spans = vec![
+                Span::styled(mark, Style::default().fg(Color::Magenta)),
                 Span::styled(
-                    format!("{:<10} ", format_size(f.size)),
+                    format!(
+                        "{:<10} ",
+                        format_amount(f.size_for(app.size_mode), app.size_mode)
+                    ),
                     Style::default().fg(Color::Yellow),
                 ),
-                name_styled,
-            ]);
+            ];
+            let share = if parent_size > 0 {
+                f.size_for(app.size_mode) as f64 / parent_size as f64
+            } else {
+                0.0
+            };
+            for column in app.visible_columns() {
+                spans.push(match column {
+                    Column::Bar => Span::styled(
+                        format!("[{}] ", usage_bar(share, 10)),
+                        Style::default().fg(Color::Cyan),
+                    ),
+                    Column::Percent => Span::raw(format!("{:>5.1}% ", share * 100.0)),
+                    Column::Count => Span::styled(
+                        match f.children_count {
+                            Some(count) => format!("{:>9} ", format_count(count as u64)),
+                            None => " ".repeat(10),
+                        },
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    // Directories created on the fly have no known time.
+                    Column::Modified => Span::styled(
+                        if f.modified == UNIX_EPOCH {
+                            " ".repeat(17)
+                        } else {
+                            format!("{} ", format_date(f.modified))
+                        },
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                });
+            }
+            spans.push(name_styled);
+            if incomplete {
+                spans.push(Span::styled(
+                    " [incomplete]",
+                    Style::default().fg(Color::Red),
+                ));
+            }
+            if f.is_mount_point {
+                let marker = if f.children_count == Some(0) {
+                    " [mount point, Enter to scan]"
+                } else {
+                    " [mount point]"
+                };
+                spans.push(Span::styled(marker, Style::default().fg(Color::Magenta)));
+            }
+            if f.is_hardlink() {
+                let marker = if f.duplicate_inode {
+                    format!(" [hardlink x{}, counted elsewhere]", f.links)
+                } else {
+                    format!(" [hardlink x{}]", f.links)
+                };
+                spans.push(Span::styled(marker, Style::default().fg(Color::DarkGray)));
+            }
+            let content = Line::from(spans);
             ListItem::new(content)
         })
         .collect();
@@ -139,6 +344,46 @@ fn draw_browsing(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     f.render_stateful_widget(list, area, &mut state);
 }
 
+fn draw_errors(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let items: Vec<ListItem> = app
+        .errors
+        .iter()
+        .map(|error| {
+            let path = error
+                .path
+                .as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| "-".to_string());
+            let kind = match error.errno {
+                Some(errno) => format!("{} (errno {})", error.kind.label(), errno),
+                None => error.kind.label().to_string(),
+            };
+            ListItem::new(vec![
+                Line::from(vec![
+                    Span::styled(format!("{:<28} ", kind), Style::default().fg(Color::Red)),
+                    Span::raw(path),
+                ]),
+                Line::from(Span::styled(
+                    format!("  {}", error.message),
+                    Style::default().fg(Color::DarkGray),
+                )),
+            ])
+        })
+        .collect();
+
+    let title = format!("Scan errors ({}) - [Esc] Back", app.errors.len());
+    let list = List::new(items)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD));
+
+    let mut state = ratatui::widgets::ListState::default();
+    if !app.errors.is_empty() {
+        state.select(Some(app.error_selection));
+    }
+
+    f.render_stateful_widget(list, area, &mut state);
+}
+
 fn draw_about(f: &mut Frame, area: ratatui::layout::Rect) {
     let text = vec![
         Line::from(Span::styled(
@@ -213,7 +458,8 @@ fn draw_delete_popup(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
                 path.to_string_lossy(),
                 Style::default().fg(Color::Yellow),
             )),
-            Line::from(""),
+            removal_line(app, " this item."),
+            limit_line(app),
             Line::from(vec![
                 Span::raw("Press "),
                 Span::styled("[y]", Style::default().add_modifier(Modifier::BOLD)),
@@ -240,6 +486,54 @@ fn draw_delete_popup(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     }
 }
 
+/// "This will PERMANENTLY DELETE ...", or where the items go instead,
+/// depending on the cleaner in use.
+fn removal_line<'a>(app: &App, what: &'a str) -> Line<'a> {
+    let action = match app.cleaner.removal() {
+        Removal::Permanent | Removal::Purge => Span::styled(
+            "PERMANENTLY DELETE",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ),
+        Removal::Trash => Span::styled(
+            "MOVE TO TRASH",
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Removal::Quarantine => Span::styled(
+            "QUARANTINE (undo with u)",
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Removal::DryRun => Span::styled(
+            "PRETEND TO DELETE (dry run)",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ),
+    };
+    Line::from(vec![Span::raw("This will "), action, Span::raw(what)])
+}
+
+/// A blank line, or a warning while an operation over the safety limits
+/// waits for its second confirmation.
+fn limit_line(app: &App) -> Line<'static> {
+    match app.over_limit {
+        Some((bytes, items)) => Line::from(Span::styled(
+            format!(
+                "Removes {} in {} items, over the limit of {} / {} items. Confirm again to go ahead.",
+                format_size(bytes),
+                items,
+                format_size(app.safety.max_bytes),
+                app.safety.max_items
+            ),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )),
+        None => Line::from(""),
+    }
+}
+
 fn draw_dashboard_cleanup_popup(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     if let Some(rec) = app.recommendations.get(app.recommendation_selection) {
         let block = Block::default()
@@ -258,17 +552,13 @@ fn draw_dashboard_cleanup_popup(f: &mut Frame, app: &App, area: ratatui::layout:
                     .fg(Color::Yellow)
                     .add_modifier(Modifier::BOLD),
             )),
-            Line::from(Span::raw(format!("Size: {}", format_size(rec.size)))),
-            Line::from(""),
-            Line::from(vec![
-                Span::raw("This will "),
-                Span::styled(
-                    "PERMANENTLY DELETE",
-                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-                ),
-                Span::raw(" all items in this category."),
-            ]),
+            Line::from(Span::raw(format!(
+                "Size: {}",
+                format_amount(rec.size, app.size_mode)
+            ))),
             Line::from(""),
+            removal_line(app, " all items in this category."),
+            limit_line(app),
             Line::from(vec![
                 Span::raw("Press "),
                 Span::styled("[y]", Style::default().add_modifier(Modifier::BOLD)),
@@ -1,3 +1,4 @@
+use crate::domain::entities::FileType;
 use ratatui::style::Color;
 
 #[allow(dead_code)]
@@ -16,3 +17,18 @@ impl Default for Theme {
         }
     }
 }
+
+/// Colour of an entry by kind; `None` for plain files.
+pub fn entry_color(is_dir: bool, file_type: &FileType) -> Option<Color> {
+    if is_dir {
+        return Some(Color::Blue);
+    }
+    match file_type {
+        FileType::Log => Some(Color::Red),
+        FileType::Cache | FileType::NpmCache | FileType::ComposerCache | FileType::AptCache => {
+            Some(Color::Yellow)
+        }
+        FileType::Docker => Some(Color::Cyan),
+        FileType::Normal => None,
+    }
+}
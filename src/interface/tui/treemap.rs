@@ -0,0 +1,103 @@
+use crate::application::app::App;
+use crate::domain::treemap;
+use crate::interface::tui::theme;
+use crate::utils::format_amount;
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, BorderType, Borders, Paragraph},
+    Frame,
+};
+
+/// Where the tiles go within the view's `area`: inside its border.
+pub fn tiles_area(area: Rect) -> treemap::Rect {
+    let inner = Block::default().borders(Borders::ALL).inner(area);
+    treemap::Rect::new(
+        inner.x as f64,
+        inner.y as f64,
+        inner.width as f64,
+        inner.height as f64,
+    )
+}
+
+pub fn draw_treemap(f: &mut Frame, app: &App, area: Rect) {
+    let tiles = app.treemap_layout(tiles_area(area));
+
+    let selected = tiles
+        .get(app.treemap_selection)
+        .map(|&(id, _)| {
+            let node = app.tree.node(id);
+            format!(
+                " | {}{} {}",
+                app.tree.name(id).to_string_lossy(),
+                if node.is_dir { "/" } else { "" },
+                format_amount(node.size(app.size_mode), app.size_mode)
+            )
+        })
+        .unwrap_or_default();
+    let title = format!(
+        " Treemap ({}){} ",
+        format_amount(
+            app.tree.node(app.current_dir).size(app.size_mode),
+            app.size_mode
+        ),
+        selected
+    );
+    f.render_widget(Block::default().title(title).borders(Borders::ALL), area);
+
+    for (i, &(id, tile)) in tiles.iter().enumerate() {
+        // Snap to cells so neighbouring tiles share edges without gaps.
+        let x = tile.x.round() as u16;
+        let y = tile.y.round() as u16;
+        let cell = Rect::new(
+            x,
+            y,
+            (tile.right().round() as u16).saturating_sub(x),
+            (tile.bottom().round() as u16).saturating_sub(y),
+        );
+        if cell.width == 0 || cell.height == 0 {
+            continue;
+        }
+
+        let node = app.tree.node(id);
+        let color = theme::entry_color(node.is_dir, &node.file_type).unwrap_or(Color::DarkGray);
+        let is_selected = i == app.treemap_selection;
+        let name = app.tree.name(id).to_string_lossy();
+        let label = if node.is_dir {
+            format!("{}/", name)
+        } else {
+            name.into_owned()
+        };
+        let size = format_amount(node.size(app.size_mode), app.size_mode);
+
+        let fill = if is_selected {
+            Style::default()
+                .bg(Color::White)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().bg(color).fg(Color::Black)
+        };
+        let tile_widget = if cell.width >= 4 && cell.height >= 3 {
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .border_type(if is_selected {
+                    BorderType::Thick
+                } else {
+                    BorderType::Plain
+                })
+                .title(label)
+                .style(fill);
+            Paragraph::new(Line::from(size)).block(block)
+        } else {
+            Paragraph::new(Line::from(label)).style(fill)
+        };
+        f.render_widget(tile_widget, cell);
+    }
+
+    if tiles.is_empty() {
+        let inner = Block::default().borders(Borders::ALL).inner(area);
+        f.render_widget(Paragraph::new(" Nothing to show here"), inner);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::application::app::App;
+use crate::interface::trends::{self, SeriesRow};
+use crate::utils::{format_date, format_delta, format_size, sparkline};
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Sparkline},
+    Frame,
+};
+
+pub fn draw_trends(f: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let records = &app.history;
+    let (Some(first), Some(last)) = (records.first(), records.last()) else {
+        let text = Paragraph::new(format!(
+            " No history for {} yet. Run scans with --history to record it.",
+            app.tree.root_path().display()
+        ))
+        .block(Block::default().title(" Trends ").borders(Borders::ALL));
+        f.render_widget(text, area);
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(7), // Total chart
+                Constraint::Min(1),    // Per-entry rows
+            ]
+            .as_ref(),
+        )
+        .split(area);
+
+    // Total size over time, with the projection underneath
+    let top = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)].as_ref())
+        .split(chunks[0].inner(&ratatui::layout::Margin {
+            horizontal: 1,
+            vertical: 1,
+        }));
+    let totals: Vec<u64> = records.iter().map(|r| r.size).collect();
+    let title = format!(
+        " Trends: {} -> {} ({}) | {} scans, {} .. {} UTC ",
+        format_size(first.size),
+        format_size(last.size),
+        format_delta(last.size as i64 - first.size as i64),
+        records.len(),
+        format_date(first.time),
+        format_date(last.time)
+    );
+    f.render_widget(
+        Block::default().title(title).borders(Borders::ALL),
+        chunks[0],
+    );
+    f.render_widget(
+        Sparkline::default()
+            .data(&totals)
+            .style(Style::default().fg(Color::Yellow)),
+        top[0],
+    );
+    f.render_widget(
+        Paragraph::new(Span::styled(
+            trends::projection(records),
+            Style::default().fg(Color::Cyan),
+        )),
+        top[1],
+    );
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+        .split(chunks[1]);
+    draw_rows(
+        f,
+        " Top-level entries ",
+        &trends::top_level_rows(records),
+        columns[0],
+    );
+    draw_rows(
+        f,
+        " File types ",
+        &trends::file_type_rows(records),
+        columns[1],
+    );
+}
+
+fn draw_rows(f: &mut Frame, title: &str, rows: &[SeriesRow], area: ratatui::layout::Rect) {
+    let width = rows.iter().map(|row| row.name.len()).max().unwrap_or(0);
+    let items: Vec<ListItem> = rows
+        .iter()
+        .map(|row| {
+            let delta_style = if row.delta() > 0 {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default().fg(Color::Green)
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:<width$} ", row.name, width = width),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("{} ", sparkline(&row.values)),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw(format!("{:<10} ", format_size(row.last()))),
+                Span::styled(format_delta(row.delta()), delta_style),
+            ]))
+        })
+        .collect();
+    f.render_widget(
+        List::new(items).block(Block::default().title(title).borders(Borders::ALL)),
+        area,
+    );
+}
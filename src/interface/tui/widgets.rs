@@ -1,3 +1,17 @@
-// Custom widgets like DiskUsageBar, FileTree will go here
-#[allow(dead_code)]
-pub struct DiskUsageBar;
+use crate::utils::usage_bar;
+use ratatui::{
+    style::{Color, Style},
+    text::Span,
+};
+
+/// Usage bar coloured by how full it is.
+pub fn disk_usage_bar(ratio: f64, width: usize) -> Span<'static> {
+    let color = if ratio >= 0.9 {
+        Color::Red
+    } else if ratio >= 0.75 {
+        Color::Yellow
+    } else {
+        Color::Green
+    };
+    Span::styled(usage_bar(ratio, width), Style::default().fg(color))
+}
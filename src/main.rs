@@ -1,24 +1,210 @@
-use clap::Parser;
+use anyhow::Context;
+use clap::{Parser, Subcommand};
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use diskdoc::application::app::{App, AppMode};
-use diskdoc::domain::ports::Scanner; // To use scan method
+use diskdoc::domain::diff::TreeDiff;
+use diskdoc::domain::entities::{Column, ScanOptions, SizeMode, SortKey, SortSpec};
+use diskdoc::domain::history::HistoryRecord;
+use diskdoc::domain::ports::{Analyzer, AuditLog, Cleaner, DryRunLog, MountLister, Scanner};
+use diskdoc::domain::treemap::Direction as TileDirection;
+use diskdoc::infrastructure::audit::AuditStore;
 use diskdoc::infrastructure::cleaner::FsCleaner;
 use diskdoc::infrastructure::docker::DockerAnalyzerImpl;
+use diskdoc::infrastructure::dry_run::{DryRunAnalyzer, DryRunCleaner};
+use diskdoc::infrastructure::history::HistoryStore;
+use diskdoc::infrastructure::mounts::SysinfoMounts;
+use diskdoc::infrastructure::ncdu::{self, NcduScanner};
+use diskdoc::infrastructure::quarantine::{PurgePolicy, QuarantineCleaner};
+use diskdoc::infrastructure::scanner::exclude::{self, ExcludeMatcher};
+use diskdoc::infrastructure::scanner::progress;
 use diskdoc::infrastructure::scanner::FsScanner;
-use diskdoc::interface::tui;
+use diskdoc::infrastructure::snapshot::{self, SnapshotStore};
+use diskdoc::infrastructure::trash::TrashCleaner;
+use diskdoc::interface::report::{DryRunReport, Report, StreamRecord};
+use diskdoc::interface::{audit, dry_run, filesystems, trends, tui};
+use diskdoc::utils::{format_duration, format_size};
 use ratatui::{backend::CrosstermBackend, Terminal};
-use std::{io, time::Duration};
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, args_conflicts_with_subcommands = true)]
 struct Args {
-    /// Path to start scanning from
-    #[arg(default_value = ".")]
-    path: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Path to start scanning from; without one the interface opens on the
+    /// filesystem overview (reports and snapshots default to ".")
+    path: Option<String>,
+
+    /// Use apparent sizes rather than disk usage
+    #[arg(long, global = true)]
+    apparent_size: bool,
+
+    /// Sort entries by size, name, modified, count, type or extension
+    #[arg(long, value_name = "KEY", value_parser = parse_sort_key, global = true)]
+    sort: Option<SortKey>,
+
+    /// Reverse the sort key's natural order
+    #[arg(long, global = true)]
+    reverse: bool,
+
+    /// List directories before files
+    #[arg(long, global = true)]
+    dirs_first: bool,
+
+    /// Browser columns to show, comma-separated: bar, percent, count, modified
+    #[arg(long, value_name = "LIST", value_delimiter = ',', value_parser = parse_column)]
+    columns: Option<Vec<Column>>,
+
+    /// Count entries instead of bytes, to find what exhausts inodes
+    #[arg(long, global = true, conflicts_with = "apparent_size")]
+    inodes: bool,
+
+    /// Stay on the filesystem of the scanned path; other mounts are listed but not walked
+    #[arg(short = 'x', long)]
+    one_file_system: bool,
+
+    /// Skip entries matching a glob pattern (repeatable)
+    #[arg(long, value_name = "PATTERN")]
+    exclude: Vec<String>,
+
+    /// Read exclude patterns from a file, one per line
+    #[arg(long, value_name = "FILE")]
+    exclude_from: Option<PathBuf>,
+
+    /// Honor .gitignore, .ignore and .diskdocignore files
+    #[arg(long)]
+    ignore_files: bool,
+
+    /// Print a JSON report instead of starting the interface
+    #[arg(long, conflicts_with = "ndjson")]
+    json: bool,
+
+    /// Stream scan events as newline-delimited JSON, ending with the report
+    #[arg(long)]
+    ndjson: bool,
+
+    /// Number of entries listed in the report's top lists
+    #[arg(long, value_name = "N", default_value_t = 20)]
+    top: usize,
+
+    /// Browse an ncdu JSON export instead of scanning the disk
+    #[arg(long, value_name = "FILE")]
+    import: Option<PathBuf>,
+
+    /// Write the scan as an ncdu JSON export ("-" for stdout) instead of starting the interface
+    #[arg(long, value_name = "FILE")]
+    export: Option<PathBuf>,
+
+    /// Save the finished scan as a snapshot in the cache directory
+    #[arg(long)]
+    save: bool,
+
+    /// Open a saved snapshot instead of scanning; defaults to the latest one of PATH
+    #[arg(long, value_name = "SNAPSHOT", num_args = 0..=1, conflicts_with = "import")]
+    load: Option<Option<PathBuf>>,
+
+    /// List saved snapshots and exit
+    #[arg(long)]
+    snapshots: bool,
+
+    /// Append a summary of the finished scan to the usage history
+    #[arg(long)]
+    history: bool,
+
+    /// Move deleted items to the freedesktop.org trash instead of removing them
+    #[arg(long, global = true, env = "DISKDOC_TRASH")]
+    trash: bool,
+
+    /// Audit log to append deletions and prunes to, e.g. one shared by all users
+    #[arg(long, value_name = "FILE", global = true, env = "DISKDOC_AUDIT_LOG")]
+    audit_log: Option<PathBuf>,
+
+    /// Only record what deletions and prunes would remove, without removing anything
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Delete for good instead of keeping deleted items in the quarantine for undo
+    #[arg(long, global = true, conflicts_with = "trash")]
+    no_quarantine: bool,
+
+    /// Purge quarantined items after this many days
+    #[arg(
+        long,
+        value_name = "DAYS",
+        default_value_t = 7,
+        global = true,
+        env = "DISKDOC_QUARANTINE_DAYS"
+    )]
+    quarantine_days: u64,
+
+    /// Purge the oldest quarantined items beyond this size, e.g. 500M or 20G; larger items are refused
+    #[arg(
+        long,
+        value_name = "SIZE",
+        value_parser = parse_size,
+        global = true,
+        env = "DISKDOC_QUARANTINE_SIZE"
+    )]
+    quarantine_size: Option<u64>,
+
+    /// Never delete this path, what is inside it or what contains it (repeatable)
+    #[arg(
+        long,
+        value_name = "PATH",
+        global = true,
+        env = "DISKDOC_PROTECT",
+        value_delimiter = ':'
+    )]
+    protect: Vec<String>,
+
+    /// Ask twice before one operation removes more than this, e.g. 500M or 20G
+    #[arg(long, value_name = "SIZE", value_parser = parse_size, global = true)]
+    max_delete_size: Option<u64>,
+
+    /// Ask twice before one operation removes more than this many items
+    #[arg(long, value_name = "N", global = true)]
+    max_delete_items: Option<usize>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Compare two saved snapshots and browse what grew or shrank
+    Diff {
+        /// The earlier snapshot
+        old: PathBuf,
+        /// The later snapshot
+        new: PathBuf,
+    },
+    /// List mounted filesystems with their space and inode usage
+    Df,
+    /// Show how a path's usage grew over the recorded scans
+    History {
+        /// Path whose history to show
+        #[arg(default_value = ".")]
+        path: String,
+    },
+    /// Show who deleted or pruned what, and when
+    Log {
+        /// Only show removals of this path or anything below it
+        path: Option<PathBuf>,
+        /// Show at most this many of the most recent records
+        #[arg(short = 'n', long, value_name = "N")]
+        limit: Option<usize>,
+    },
+    /// Restore the most recent deletions from the quarantine
+    Undo {
+        /// Number of operations to restore, newest first; a batch or cleanup counts as one
+        #[arg(default_value_t = 1)]
+        count: usize,
+    },
 }
 
 #[tokio::main]
@@ -26,6 +212,279 @@ async fn main() -> anyhow::Result<()> {
     // Setup CLI args
     let args = Args::parse();
 
+    if let Some(Command::Diff { old, new }) = &args.command {
+        return run_diff(&args, old, new).await;
+    }
+    if let Some(Command::Df) = &args.command {
+        let filesystems = SysinfoMounts::new().filesystems();
+        filesystems::print(&filesystems, io::stdout().lock())?;
+        return Ok(());
+    }
+    if let Some(Command::History { path }) = &args.command {
+        let root = canonical(path);
+        let records = HistoryStore::new()?.records_for(&root)?;
+        trends::print(&root, &records, io::stdout().lock())?;
+        return Ok(());
+    }
+    if let Some(Command::Log { path, limit }) = &args.command {
+        let mut records = args.audit_store()?.records()?;
+        if let Some(path) = path {
+            let path = std::path::absolute(path)?;
+            records.retain(|record| Path::new(&record.entry.target).starts_with(&path));
+        }
+        if let Some(limit) = limit {
+            records.drain(..records.len().saturating_sub(*limit));
+        }
+        audit::print(&records, io::stdout().lock())?;
+        return Ok(());
+    }
+    if let Some(Command::Undo { count }) = &args.command {
+        let quarantine = args.quarantine()?;
+        if args.dry_run {
+            let pending = quarantine.pending_undo(*count)?;
+            if pending.is_empty() {
+                println!("Nothing to undo");
+            }
+            for entry in pending {
+                println!("Would restore {}", entry.original.display());
+            }
+            return Ok(());
+        }
+        let restored = quarantine.undo(*count)?;
+        if restored.is_empty() {
+            println!("Nothing to undo");
+        }
+        for path in restored {
+            println!("Restored {}", path.display());
+        }
+        return Ok(());
+    }
+    if args.snapshots {
+        return list_snapshots();
+    }
+
+    let mut excludes = args.exclude.clone();
+    if let Some(file) = &args.exclude_from {
+        excludes.extend(exclude::read_patterns(file)?);
+    }
+
+    // Fail early on invalid patterns or inputs, before the terminal is taken over.
+    let mut scan_options = ScanOptions::default();
+    let mut loaded = None;
+    let (path, scanner): (PathBuf, Box<dyn Scanner>) = if let Some(file) = &args.import {
+        let scanner = NcduScanner::open(file)?;
+        (scanner.root().to_path_buf(), Box::new(scanner))
+    } else if let Some(load) = &args.load {
+        let file = match load {
+            Some(file) => file.clone(),
+            None => {
+                let root = canonical(args.path());
+                SnapshotStore::new()?
+                    .latest_for(&root)?
+                    .with_context(|| format!("No saved snapshot of {}", root.display()))?
+            }
+        };
+        // Build the tree straight from the parsed snapshot rather than
+        // replaying it through the scan channel and the app's event loop.
+        let (meta, tree, errors) = snapshot::load_tree(&file)?;
+        scan_options = meta.options.clone();
+        let root = meta.root.clone();
+        loaded = Some((meta, tree, errors));
+        (
+            root,
+            Box::new(FsScanner::with_options(scan_options.clone())),
+        )
+    } else {
+        let path = canonical(args.path());
+        ExcludeMatcher::new(&path, &excludes)?;
+        scan_options = ScanOptions {
+            one_file_system: args.one_file_system,
+            excludes,
+            use_ignore_files: args.ignore_files,
+        };
+        let scanner = FsScanner::with_options(scan_options.clone());
+        (path, Box::new(scanner))
+    };
+
+    let mut app = new_app(&args, &path, scanner)?;
+    if let Some((meta, tree, errors)) = loaded {
+        app.snapshot = Some(meta);
+        app.tree = tree;
+        app.current_dir = app.tree.root();
+        app.errors = errors;
+        app.is_scanning = false;
+        app.mode = AppMode::Browsing;
+    }
+    app.import = args.import.clone();
+
+    if args.json || args.ndjson || args.export.is_some() {
+        run_headless(&args, &mut app, &path)?;
+        save_snapshot(&args, &app, &scan_options)?;
+        return record_history(&args, &app, &path);
+    }
+
+    if args.path.is_none() && args.import.is_none() && args.load.is_none() {
+        // No path given: let the user pick a filesystem to scan.
+        app.is_scanning = false;
+        app.show_filesystems();
+    } else {
+        app.history = load_history(&path);
+        if app.snapshot.is_none() {
+            app.start_scan(&path);
+        }
+    }
+    if app.read_only_reason().is_none() {
+        args.purge_quarantine();
+    }
+    run_tui(&mut app).await?;
+    if let Some(log) = &app.dry_run {
+        dry_run::print(log, app.size_mode, io::stdout().lock())?;
+    }
+
+    save_snapshot(&args, &app, &scan_options)?;
+    let root = app.tree.root_path().to_path_buf();
+    record_history(&args, &app, &root)
+}
+
+fn parse_sort_key(key: &str) -> Result<SortKey, String> {
+    SortKey::from_key(key).ok_or_else(|| {
+        let known: Vec<&str> = SortKey::ALL.iter().map(|sort_key| sort_key.key()).collect();
+        format!(
+            "unknown sort key '{}', expected one of: {}",
+            key,
+            known.join(", ")
+        )
+    })
+}
+
+fn parse_column(key: &str) -> Result<Column, String> {
+    Column::from_key(key).ok_or_else(|| {
+        let known: Vec<&str> = Column::ALL.iter().map(|column| column.key()).collect();
+        format!(
+            "unknown column '{}', expected one of: {}",
+            key,
+            known.join(", ")
+        )
+    })
+}
+
+/// Parses a size such as `1024`, `500M` or `20G` (binary units).
+fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let digits = value.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let unit = value[digits.len()..].to_ascii_uppercase();
+    let shift = match unit.trim_end_matches("IB").trim_end_matches('B') {
+        "" => 0,
+        "K" => 10,
+        "M" => 20,
+        "G" => 30,
+        "T" => 40,
+        _ => {
+            return Err(format!(
+                "unknown size unit '{}', expected K, M, G or T",
+                unit
+            ))
+        }
+    };
+    let number: u64 = digits
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid size '{}'", value))?;
+    number
+        .checked_mul(1 << shift)
+        .ok_or_else(|| format!("size '{}' is too large", value))
+}
+
+impl Args {
+    fn path(&self) -> &str {
+        self.path.as_deref().unwrap_or(".")
+    }
+
+    fn sort_spec(&self) -> SortSpec {
+        let mut spec = SortSpec::new(self.sort.unwrap_or(SortKey::Size));
+        if self.reverse {
+            spec.order = spec.order.reverse();
+        }
+        spec.dirs_first = self.dirs_first;
+        spec
+    }
+
+    fn cleaner(&self) -> anyhow::Result<Box<dyn Cleaner>> {
+        Ok(if self.trash {
+            Box::new(TrashCleaner::new()?)
+        } else if self.no_quarantine {
+            Box::new(FsCleaner::new())
+        } else {
+            Box::new(self.quarantine()?)
+        })
+    }
+
+    /// Purges expired quarantined items at startup too, not only after the
+    /// next deletion. Dry runs leave the quarantine alone.
+    fn purge_quarantine(&self) {
+        if self.trash || self.no_quarantine || self.dry_run {
+            return;
+        }
+        if let Ok(quarantine) = self.quarantine() {
+            let _ = quarantine.purge();
+        }
+    }
+
+    fn audit_store(&self) -> anyhow::Result<AuditStore> {
+        match &self.audit_log {
+            Some(path) => Ok(AuditStore::with_path(path)),
+            None => AuditStore::new(),
+        }
+    }
+
+    /// The quarantine, recording its purges in the audit log.
+    fn quarantine(&self) -> anyhow::Result<QuarantineCleaner> {
+        let quarantine = QuarantineCleaner::new(PurgePolicy {
+            max_age: Some(Duration::from_secs(self.quarantine_days * 86_400)),
+            max_size: self.quarantine_size,
+        })?;
+        Ok(quarantine.with_audit(Box::new(self.audit_store()?)))
+    }
+
+    fn size_mode(&self) -> SizeMode {
+        if self.inodes {
+            SizeMode::Count
+        } else if self.apparent_size {
+            SizeMode::Apparent
+        } else {
+            SizeMode::Disk
+        }
+    }
+}
+
+/// Recorded history of `root` for the trends view. A broken history file
+/// should not keep the interface from starting.
+fn load_history(root: &Path) -> Vec<HistoryRecord> {
+    HistoryStore::new()
+        .and_then(|store| store.records_for(root))
+        .unwrap_or_default()
+}
+
+/// Opens the diff view comparing two snapshots.
+async fn run_diff(args: &Args, old: &Path, new: &Path) -> anyhow::Result<()> {
+    let (_, old_tree, _) = snapshot::load_tree(old)?;
+    let (_, new_tree, _) = snapshot::load_tree(new)?;
+
+    // The diff view never deletes anything.
+    let mut app = App::new(
+        new_tree.root_path().display().to_string(),
+        Box::new(FsCleaner::new()),
+        Box::new(DockerAnalyzerImpl::new()),
+    );
+    app.size_mode = args.size_mode();
+    app.sort.order = args.sort_spec().order;
+    app.is_scanning = false;
+    app.open_diff(TreeDiff::new(old_tree, new_tree));
+    run_tui(&mut app).await
+}
+
+/// Takes over the terminal and runs the interface until the user quits.
+async fn run_tui(app: &mut App) -> anyhow::Result<()> {
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -33,26 +492,8 @@ async fn main() -> anyhow::Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // Create Infrastructure Adapters
-    let cleaner = Box::new(FsCleaner::new());
-    let analyzer = Box::new(DockerAnalyzerImpl::new());
-    let scanner = FsScanner::new();
-
-    // Create app with dependencies
-    let mut app = App::new(args.path.clone(), cleaner, analyzer);
-
-    // Start scanner
-    let (tx, rx) = std::sync::mpsc::channel();
-    app.scan_receiver = Some(rx);
-
-    let path = std::path::PathBuf::from(&args.path);
-    // Use scanner trait method or direct?
-    // Using trait method is better but FsScanner::scan takes &self.
-    // We can just call it direct or via trait.
-    scanner.scan(&path, tx);
-
     // Run app loop
-    let res = run_app(&mut terminal, &mut app).await;
+    let res = run_app(&mut terminal, app).await;
 
     // Restore terminal
     disable_raw_mode()?;
@@ -70,6 +511,165 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Canonical form of the scan path, so snapshots of the same directory
+/// match however it was spelled.
+fn canonical(path: &str) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path))
+}
+
+fn new_app(args: &Args, path: &Path, scanner: Box<dyn Scanner>) -> anyhow::Result<App> {
+    // Create Infrastructure Adapters
+    let dry_run = args.dry_run.then(DryRunLog::new);
+    let (cleaner, analyzer): (Box<dyn Cleaner>, Box<dyn Analyzer>) = match &dry_run {
+        Some(log) => (
+            Box::new(DryRunCleaner::new(log.clone())),
+            Box::new(DryRunAnalyzer::new(
+                Box::new(DockerAnalyzerImpl::new()),
+                log.clone(),
+            )),
+        ),
+        None => (args.cleaner()?, Box::new(DockerAnalyzerImpl::new())),
+    };
+
+    // Create app with dependencies
+    let mut app = App::new(path.display().to_string(), cleaner, analyzer);
+    app.dry_run = dry_run;
+    app.audit = Some(Box::new(args.audit_store()?));
+    if let Some(home) = std::env::var_os("HOME") {
+        app.safety.keep.push(canonical(&home.to_string_lossy()));
+    }
+    app.safety
+        .protected
+        .extend(args.protect.iter().map(|path| canonical(path)));
+    if let Some(bytes) = args.max_delete_size {
+        app.safety.max_bytes = bytes;
+    }
+    if let Some(items) = args.max_delete_items {
+        app.safety.max_items = items;
+    }
+    app.size_mode = args.size_mode();
+    app.sort = args.sort_spec();
+    if let Some(columns) = &args.columns {
+        app.columns = columns.clone();
+    }
+    app.scanner = Some(scanner);
+    app.mounts = Some(Box::new(SysinfoMounts::new()));
+    Ok(app)
+}
+
+/// Saves the scan if `--save` was given and the scan ran to completion.
+fn save_snapshot(args: &Args, app: &App, options: &ScanOptions) -> anyhow::Result<()> {
+    if !args.save {
+        return Ok(());
+    }
+    if app.is_scanning || app.is_partial {
+        eprintln!("Scan did not finish; snapshot not saved.");
+        return Ok(());
+    }
+    if app.is_simulated() {
+        eprintln!("Dry run removed entries from the results; snapshot not saved.");
+        return Ok(());
+    }
+    let path = SnapshotStore::new()?.save(&app.tree, &app.errors, options)?;
+    eprintln!("Snapshot saved to {}", path.display());
+    Ok(())
+}
+
+/// Appends the scan to the usage history if `--history` was given and the
+/// scan ran to completion. Loaded snapshots are not recorded again.
+fn record_history(args: &Args, app: &App, path: &Path) -> anyhow::Result<()> {
+    if !args.history || app.snapshot.is_some() || args.import.is_some() {
+        return Ok(());
+    }
+    if app.is_scanning || app.is_partial {
+        eprintln!("Scan did not finish; history not recorded.");
+        return Ok(());
+    }
+    if app.is_simulated() {
+        eprintln!("Dry run removed entries from the results; history not recorded.");
+        return Ok(());
+    }
+    let record = HistoryRecord::from_tree(&app.tree, progress::filesystem_space(path));
+    HistoryStore::new()?.append(&record)
+}
+
+fn list_snapshots() -> anyhow::Result<()> {
+    let store = SnapshotStore::new()?;
+    let snapshots = store.list()?;
+    if snapshots.is_empty() {
+        println!("No snapshots in {}", store.dir().display());
+    }
+    for (path, meta) in snapshots {
+        let age = SystemTime::now()
+            .duration_since(meta.created)
+            .unwrap_or_default();
+        println!(
+            "{}  {} ago on {}  {} in {} entries\n  {}",
+            meta.root.display(),
+            format_duration(age),
+            meta.host,
+            format_size(meta.allocated_size),
+            meta.entries,
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Scans to completion without touching the terminal, then writes the
+/// requested export and report.
+fn run_headless(args: &Args, app: &mut App, path: &Path) -> anyhow::Result<()> {
+    if app.snapshot.is_none() {
+        app.start_scan(path);
+    }
+
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    if args.ndjson {
+        let mut result = Ok(());
+        app.wait_for_scan(|event| {
+            if let (Ok(()), Some(record)) = (&result, StreamRecord::from_event(event)) {
+                result = write_line(&mut out, &record);
+            }
+        });
+        result?;
+    } else {
+        app.wait_for_scan(|_| {});
+    }
+
+    if let Some(file) = &args.export {
+        if file.as_os_str() == "-" {
+            ncdu::export(&app.tree, &app.errors, &mut out)?;
+        } else {
+            let export = File::create(file)
+                .with_context(|| format!("Failed to create export: {}", file.display()))?;
+            ncdu::export(&app.tree, &app.errors, io::BufWriter::new(export))?;
+        }
+    }
+
+    if args.json || args.ndjson {
+        let mut report = Report::build(app, args.top);
+        if let Some(log) = app.dry_run.clone() {
+            // Report the scan as found, then what the cleanups would remove.
+            app.clean_all_recommendations();
+            report.dry_run = Some(DryRunReport::new(&log, app.size_mode));
+        }
+        if args.json {
+            serde_json::to_writer_pretty(&mut out, &report)?;
+            writeln!(out)?;
+        } else {
+            write_line(&mut out, &StreamRecord::Report(&report))?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn write_line(out: &mut impl Write, record: &StreamRecord) -> anyhow::Result<()> {
+    serde_json::to_writer(&mut *out, record)?;
+    writeln!(out)?;
+    Ok(())
+}
+
 async fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: &mut App,
@@ -80,6 +680,13 @@ async fn run_app<B: ratatui::backend::Backend>(
         if crossterm::event::poll(Duration::from_millis(100))? {
             if let Event::Key(key) = crossterm::event::read()? {
                 match app.mode {
+                    AppMode::Scanning => match key.code {
+                        KeyCode::Char('q') => return Ok(()),
+                        KeyCode::Char('x') | KeyCode::Esc => app.cancel_scan(),
+                        KeyCode::Char('p') => app.toggle_pause(),
+                        KeyCode::Char('2') => app.mode = AppMode::Browsing,
+                        _ => {}
+                    },
                     AppMode::DeleteConfirmation => match key.code {
                         KeyCode::Char('y') | KeyCode::Enter => app.confirm_delete(),
                         KeyCode::Char('n') | KeyCode::Char('q') | KeyCode::Esc => {
@@ -87,6 +694,30 @@ async fn run_app<B: ratatui::backend::Backend>(
                         }
                         _ => {}
                     },
+                    AppMode::Marked => match key.code {
+                        KeyCode::Char('q') => return Ok(()),
+                        KeyCode::Esc | KeyCode::Char('m') => app.mode = AppMode::Browsing,
+                        KeyCode::Down | KeyCode::Char('j') => app.marked_next(),
+                        KeyCode::Up | KeyCode::Char('k') => app.marked_prev(),
+                        KeyCode::Char(' ') => app.unmark_selected(),
+                        KeyCode::Char('d') => app.request_batch_delete(),
+                        KeyCode::Char('u') => app.undo_last(),
+                        _ => {}
+                    },
+                    AppMode::Audit => match key.code {
+                        KeyCode::Char('q') => return Ok(()),
+                        KeyCode::Esc | KeyCode::Char('L') => app.mode = AppMode::Browsing,
+                        KeyCode::Down | KeyCode::Char('j') => app.audit_next(),
+                        KeyCode::Up | KeyCode::Char('k') => app.audit_prev(),
+                        _ => {}
+                    },
+                    AppMode::BatchDeleteConfirmation => match key.code {
+                        KeyCode::Char('y') | KeyCode::Enter => app.confirm_batch_delete(),
+                        KeyCode::Char('n') | KeyCode::Char('q') | KeyCode::Esc => {
+                            app.cancel_batch_delete()
+                        }
+                        _ => {}
+                    },
                     AppMode::DashboardCleanupConfirmation => match key.code {
                         KeyCode::Char('y') | KeyCode::Enter => app.confirm_clean_recommendation(),
                         KeyCode::Char('n') | KeyCode::Char('q') | KeyCode::Esc => {
@@ -94,6 +725,78 @@ async fn run_app<B: ratatui::backend::Backend>(
                         }
                         _ => {}
                     },
+                    AppMode::Diff => match key.code {
+                        KeyCode::Char('q') => return Ok(()),
+                        KeyCode::Enter | KeyCode::Right => app.diff_enter(),
+                        KeyCode::Backspace | KeyCode::Left | KeyCode::Esc => app.diff_up(),
+                        KeyCode::Down | KeyCode::Char('j') => app.diff_next(),
+                        KeyCode::Up | KeyCode::Char('k') => app.diff_prev(),
+                        KeyCode::Char('s') => app.toggle_sort(),
+                        KeyCode::Char('a') => app.size_mode = app.size_mode.toggle(),
+                        KeyCode::Char('i') => app.size_mode = app.size_mode.toggle_count(),
+                        _ => {}
+                    },
+                    AppMode::Errors => match key.code {
+                        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('e') => {
+                            app.mode = AppMode::Browsing
+                        }
+                        KeyCode::Down | KeyCode::Char('j') => app.errors_next(),
+                        KeyCode::Up | KeyCode::Char('k') => app.errors_prev(),
+                        _ => {}
+                    },
+                    AppMode::Treemap => {
+                        let area = tui::treemap::tiles_area(tui::content_area(terminal.size()?));
+                        match key.code {
+                            KeyCode::Char('q') => return Ok(()),
+                            KeyCode::Left | KeyCode::Char('h') => {
+                                app.treemap_move(TileDirection::Left, area)
+                            }
+                            KeyCode::Right | KeyCode::Char('l') => {
+                                app.treemap_move(TileDirection::Right, area)
+                            }
+                            KeyCode::Up | KeyCode::Char('k') => {
+                                app.treemap_move(TileDirection::Up, area)
+                            }
+                            KeyCode::Down | KeyCode::Char('j') => {
+                                app.treemap_move(TileDirection::Down, area)
+                            }
+                            KeyCode::Enter => app.treemap_enter(),
+                            KeyCode::Backspace | KeyCode::Esc => app.treemap_up(),
+                            KeyCode::Char('1') => {
+                                app.scan_dashboard();
+                                app.mode = AppMode::Dashboard;
+                            }
+                            KeyCode::Char('2') => app.mode = AppMode::Browsing,
+                            KeyCode::Char('a') => {
+                                app.toggle_size_mode();
+                                app.treemap_selection = 0;
+                            }
+                            KeyCode::Char('i') => {
+                                app.toggle_count_mode();
+                                app.treemap_selection = 0;
+                            }
+                            _ => {}
+                        }
+                    }
+                    AppMode::Filesystems => match key.code {
+                        KeyCode::Char('q') => return Ok(()),
+                        KeyCode::Esc | KeyCode::Char('f') => app.close_filesystems(),
+                        KeyCode::Down | KeyCode::Char('j') => app.filesystems_next(),
+                        KeyCode::Up | KeyCode::Char('k') => app.filesystems_prev(),
+                        KeyCode::Enter => {
+                            app.scan_selected_filesystem();
+                            app.history = load_history(app.tree.root_path());
+                        }
+                        _ => {}
+                    },
+                    AppMode::Trends => {
+                        if matches!(
+                            key.code,
+                            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('t')
+                        ) {
+                            app.mode = AppMode::Browsing;
+                        }
+                    }
                     AppMode::About => {
                         if key.code == KeyCode::Esc || key.code == KeyCode::Char('q') {
                             app.mode = AppMode::Browsing;
@@ -113,6 +816,38 @@ async fn run_app<B: ratatui::backend::Backend>(
                         if key.code == KeyCode::Char('2') {
                             app.mode = AppMode::Browsing;
                         }
+                        if key.code == KeyCode::Char('3') {
+                            app.treemap_selection = 0;
+                            app.mode = AppMode::Treemap;
+                        }
+
+                        if key.code == KeyCode::Char('e') {
+                            app.mode = AppMode::Errors;
+                        }
+
+                        if key.code == KeyCode::Char('t') {
+                            app.mode = AppMode::Trends;
+                        }
+
+                        if key.code == KeyCode::Char('f') {
+                            app.show_filesystems();
+                        }
+
+                        if key.code == KeyCode::Char('a') {
+                            app.toggle_size_mode();
+                        }
+
+                        if key.code == KeyCode::Char('i') {
+                            app.toggle_count_mode();
+                        }
+
+                        if key.code == KeyCode::Char('u') {
+                            app.undo_last();
+                        }
+
+                        if key.code == KeyCode::Char('L') {
+                            app.show_audit();
+                        }
 
                         if app.mode == AppMode::Dashboard {
                             if key.code == KeyCode::Esc || key.code == KeyCode::Char('q') {
@@ -126,19 +861,30 @@ async fn run_app<B: ratatui::backend::Backend>(
                                 }
                                 _ => {}
                             }
-                            return Ok(());
-                        }
-
-                        if key.code == KeyCode::Char('s') {
-                            app.toggle_sort();
-                        }
-                        match key.code {
-                            KeyCode::Enter | KeyCode::Right => app.enter_dir(),
-                            KeyCode::Backspace | KeyCode::Left => app.go_up(),
-                            KeyCode::Down | KeyCode::Char('j') => app.date_next(),
-                            KeyCode::Up | KeyCode::Char('k') => app.date_prev(),
-                            KeyCode::Char('d') => app.request_delete(),
-                            _ => {}
+                        } else {
+                            if key.code == KeyCode::Char('s') {
+                                app.toggle_sort();
+                            }
+                            if key.code == KeyCode::Char('o') {
+                                app.cycle_sort_key();
+                            }
+                            if key.code == KeyCode::Char('g') {
+                                app.toggle_dirs_first();
+                            }
+                            if key.code == KeyCode::Char('v') {
+                                app.toggle_columns();
+                            }
+                            match key.code {
+                                KeyCode::Enter | KeyCode::Right => app.enter_dir(),
+                                KeyCode::Backspace | KeyCode::Left => app.go_up(),
+                                KeyCode::Down | KeyCode::Char('j') => app.date_next(),
+                                KeyCode::Up | KeyCode::Char('k') => app.date_prev(),
+                                KeyCode::Char('d') => app.request_delete(),
+                                KeyCode::Char(' ') => app.toggle_mark(),
+                                KeyCode::Char('m') => app.show_marked(),
+                                KeyCode::Char('r') => app.rescan_selected(),
+                                _ => {}
+                            }
                         }
                     }
                 }
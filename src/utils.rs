@@ -1,5 +1,105 @@
+use crate::domain::entities::SizeMode;
 use human_bytes::human_bytes;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 pub fn format_size(size: u64) -> String {
     human_bytes(size as f64)
 }
+
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Formats a count with thousands separators, e.g. `1,234,567`.
+pub fn format_count(count: u64) -> String {
+    let digits = count.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(digit);
+    }
+    out
+}
+
+/// Formats a value measured in `mode`: a size, or an entry count.
+pub fn format_amount(value: u64, mode: SizeMode) -> String {
+    match mode {
+        SizeMode::Count => format_count(value),
+        SizeMode::Disk | SizeMode::Apparent => format_size(value),
+    }
+}
+
+/// Like [`format_amount`], for a signed change.
+pub fn format_amount_delta(delta: i64, mode: SizeMode) -> String {
+    match mode {
+        SizeMode::Count => {
+            let sign = if delta < 0 { "-" } else { "+" };
+            format!("{}{}", sign, format_count(delta.unsigned_abs()))
+        }
+        SizeMode::Disk | SizeMode::Apparent => format_delta(delta),
+    }
+}
+
+/// Formats a size change with an explicit sign, e.g. `+1.5 MiB`.
+pub fn format_delta(delta: i64) -> String {
+    let sign = if delta < 0 { "-" } else { "+" };
+    format!("{}{}", sign, format_size(delta.unsigned_abs()))
+}
+
+/// Draws a usage ratio (0.0 to 1.0) as a bar `width` characters wide.
+pub fn usage_bar(ratio: f64, width: usize) -> String {
+    let filled = ((ratio.clamp(0.0, 1.0) * width as f64).round() as usize).min(width);
+    format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+}
+
+/// Renders values as a line of block characters scaled between their
+/// minimum and maximum, e.g. `▁▃▅█`.
+pub fn sparkline(values: &[u64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let (Some(&min), Some(&max)) = (values.iter().min(), values.iter().max()) else {
+        return String::new();
+    };
+    let range = (max - min).max(1) as f64;
+    values
+        .iter()
+        .map(|&v| BARS[((v - min) as f64 / range * 7.0).round() as usize])
+        .collect()
+}
+
+/// Formats a timestamp as a UTC date and time, e.g. `2024-03-09 14:05`.
+pub fn format_date(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+
+    // Civil date from days since the epoch (Howard Hinnant's algorithm).
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        (rem % 3_600) / 60
+    )
+}
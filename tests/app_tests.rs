@@ -1,10 +1,11 @@
 use diskdoc::application::app::{App, AppMode};
-use diskdoc::domain::entities::{FileStats, FileType, SortOrder};
+use diskdoc::domain::entities::{ScanError, ScanErrorKind, SizeMode, SortOrder};
+use diskdoc::domain::ports::{ScanEvent, ScanHandle};
+use std::io;
 use std::path::PathBuf;
-use std::time::SystemTime;
 
 mod test_utils;
-use test_utils::{MockAnalyzer, MockCleaner};
+use test_utils::{dir_entry, file_entry, MockAnalyzer, MockCleaner};
 
 fn setup_app() -> App {
     App::new(
@@ -13,53 +14,43 @@ fn setup_app() -> App {
         Box::new(MockAnalyzer::new()),
     )
 }
-fn create_file_stat(name: &str, size: u64) -> FileStats {
-    FileStats {
-        path: PathBuf::from(name),
-        size,
-        is_dir: false,
-        modified: SystemTime::UNIX_EPOCH,
-        children_count: None,
-        file_type: FileType::Normal,
-    }
-}
 
 #[test]
 fn test_app_sorting() {
     let mut app = setup_app();
 
-    app.files.push(create_file_stat("small", 100));
-    app.files.push(create_file_stat("large", 1000));
-    app.files.push(create_file_stat("medium", 500));
+    app.add_entry(file_entry("/test/small", 100));
+    app.add_entry(file_entry("/test/large", 1000));
+    app.add_entry(file_entry("/test/medium", 500));
 
     // Default is Descending
-    assert_eq!(app.sort_order, SortOrder::Desc);
+    assert_eq!(app.sort.order, SortOrder::Desc);
 
     app.toggle_sort(); // Switch to Ascending
-    assert_eq!(app.sort_order, SortOrder::Asc);
+    assert_eq!(app.sort.order, SortOrder::Asc);
 
     // Verify Ascending order
-    assert_eq!(app.files[0].size, 100);
-    assert_eq!(app.files[1].size, 500);
-    assert_eq!(app.files[2].size, 1000);
+    assert_eq!(app.get_current_files()[0].size, 100);
+    assert_eq!(app.get_current_files()[1].size, 500);
+    assert_eq!(app.get_current_files()[2].size, 1000);
 
     app.toggle_sort(); // Becomes Desc
-    assert_eq!(app.sort_order, SortOrder::Desc);
+    assert_eq!(app.sort.order, SortOrder::Desc);
 
     // Verify Descending order
-    assert_eq!(app.files[0].size, 1000);
-    assert_eq!(app.files[1].size, 500);
-    assert_eq!(app.files[2].size, 100);
+    assert_eq!(app.get_current_files()[0].size, 1000);
+    assert_eq!(app.get_current_files()[1].size, 500);
+    assert_eq!(app.get_current_files()[2].size, 100);
 
     app.toggle_sort();
-    assert_eq!(app.sort_order, SortOrder::Asc);
-    assert_eq!(app.files[0].size, 100);
+    assert_eq!(app.sort.order, SortOrder::Asc);
+    assert_eq!(app.get_current_files()[0].size, 100);
 }
 
 #[test]
 fn test_delete_confirmation_flow() {
     let mut app = setup_app();
-    app.files.push(create_file_stat("/test/file1", 100));
+    app.add_entry(file_entry("/test/file1", 100));
     app.mode = AppMode::Browsing;
 
     // Request delete
@@ -76,3 +67,306 @@ fn test_delete_confirmation_flow() {
     app.request_delete();
     assert_eq!(app.mode, AppMode::DeleteConfirmation);
 }
+
+#[test]
+fn test_directory_sizes_roll_up() {
+    let mut app = setup_app();
+    let (tx, rx) = std::sync::mpsc::channel();
+    app.scan_receiver = Some(rx);
+
+    tx.send(ScanEvent::NewEntry(dir_entry("/test/dir", 4096)))
+        .unwrap();
+    tx.send(ScanEvent::NewEntry(dir_entry("/test/dir/nested", 4096)))
+        .unwrap();
+    tx.send(ScanEvent::NewEntry(file_entry("/test/dir/a", 1000)))
+        .unwrap();
+    tx.send(ScanEvent::NewEntry(file_entry("/test/dir/nested/b", 500)))
+        .unwrap();
+    app.on_tick();
+
+    let dir = app.get_current_files()[0].clone();
+    assert_eq!(dir.size, 4096 + 4096 + 1000 + 500);
+    assert_eq!(dir.children_count, Some(3));
+    assert_eq!(app.total_size(), 4096 + 4096 + 1000 + 500);
+
+    // Deleting a nested directory removes its subtree from the ancestors.
+    app.enter_dir();
+    let nested_index = app
+        .get_current_files()
+        .iter()
+        .position(|f| f.is_dir)
+        .unwrap();
+    app.selection = nested_index;
+    app.request_delete();
+    app.confirm_delete();
+
+    app.go_up();
+    let dir = app.get_current_files()[0].clone();
+    assert_eq!(dir.size, 4096 + 1000);
+    assert_eq!(dir.children_count, Some(1));
+    assert_eq!(app.total_size(), 4096 + 1000);
+}
+
+#[test]
+fn test_size_mode_drives_sorting_and_totals() {
+    let mut app = setup_app();
+
+    // A sparse file: large apparent size, little allocated.
+    let mut sparse = file_entry("/test/sparse.img", 10_000);
+    sparse.allocated_size = 4096;
+    // A small file on a large-block filesystem.
+    let mut dense = file_entry("/test/dense.bin", 5_000);
+    dense.allocated_size = 8192;
+    app.add_entry(sparse);
+    app.add_entry(dense);
+
+    assert_eq!(app.size_mode, SizeMode::Disk);
+    assert_eq!(app.total_size(), 4096 + 8192);
+    assert_eq!(
+        app.get_current_files()[0].path,
+        PathBuf::from("/test/dense.bin")
+    );
+
+    app.toggle_size_mode();
+    assert_eq!(app.size_mode, SizeMode::Apparent);
+    assert_eq!(app.total_size(), 15_000);
+    assert_eq!(
+        app.get_current_files()[0].path,
+        PathBuf::from("/test/sparse.img")
+    );
+}
+
+#[test]
+fn test_cancel_scan_keeps_partial_results() {
+    let mut app = setup_app();
+    let (tx, rx) = std::sync::mpsc::channel();
+    let handle = ScanHandle::new();
+    app.scan_receiver = Some(rx);
+    app.scan_handle = Some(handle.clone());
+
+    tx.send(ScanEvent::NewEntry(file_entry("/test/partial", 100)))
+        .unwrap();
+    app.on_tick();
+
+    app.toggle_pause();
+    assert!(handle.is_paused());
+    assert!(app.is_paused());
+    app.toggle_pause();
+    assert!(!handle.is_paused());
+
+    app.cancel_scan();
+    assert!(handle.is_cancelled());
+    assert!(!handle.checkpoint());
+    assert!(!app.is_scanning);
+    assert!(app.is_partial);
+    assert_eq!(app.mode, AppMode::Browsing);
+    assert_eq!(app.get_current_files().len(), 1);
+}
+
+#[test]
+fn test_scan_errors_are_collected() {
+    let mut app = setup_app();
+    let (tx, rx) = std::sync::mpsc::channel();
+    app.scan_receiver = Some(rx);
+
+    tx.send(ScanEvent::NewEntry(dir_entry("/test/locked", 0)))
+        .unwrap();
+    let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+    tx.send(ScanEvent::Error(ScanError::from_io(
+        Some(PathBuf::from("/test/locked/secret")),
+        &denied,
+    )))
+    .unwrap();
+    app.on_tick();
+
+    assert_eq!(app.errors.len(), 1);
+    assert_eq!(app.errors[0].kind, ScanErrorKind::PermissionDenied);
+    let locked = app.tree.find(&PathBuf::from("/test/locked")).unwrap();
+    assert!(app.tree.node(locked).incomplete);
+    assert!(app.tree.node(app.tree.root()).incomplete);
+}
+
+#[test]
+fn test_scan_error_kinds() {
+    let vanished = ScanError::from_io(None, &io::Error::from(io::ErrorKind::NotFound));
+    assert_eq!(vanished.kind, ScanErrorKind::Vanished);
+
+    let os_error = ScanError::from_io(None, &io::Error::from_raw_os_error(5));
+    assert_eq!(os_error.errno, Some(5));
+}
+
+#[test]
+fn test_browser_columns() {
+    use diskdoc::domain::entities::Column;
+
+    let mut app = setup_app();
+    assert_eq!(
+        app.visible_columns(),
+        &[Column::Bar, Column::Percent, Column::Count]
+    );
+
+    app.columns = vec![Column::Modified];
+    app.toggle_columns();
+    assert!(app.visible_columns().is_empty());
+    app.toggle_columns();
+    assert_eq!(app.visible_columns(), &[Column::Modified]);
+
+    assert_eq!(Column::from_key("percent"), Some(Column::Percent));
+    assert_eq!(Column::from_key("size"), None);
+}
+
+#[test]
+fn test_cycle_sort_key() {
+    use diskdoc::domain::entities::SortKey;
+
+    let mut app = setup_app();
+    app.add_entry(file_entry("/test/b", 100));
+    app.add_entry(file_entry("/test/a", 1000));
+    app.toggle_dirs_first();
+
+    app.cycle_sort_key();
+    assert_eq!(app.sort.key, SortKey::Name);
+    assert_eq!(app.sort.order, SortOrder::Asc);
+    assert!(app.sort.dirs_first);
+    assert_eq!(app.get_current_files()[0].path, PathBuf::from("/test/a"));
+    assert_eq!(app.sort.label(), "name asc, dirs first");
+
+    for _ in 0..5 {
+        app.cycle_sort_key();
+    }
+    assert_eq!(app.sort.key, SortKey::Size);
+    assert_eq!(app.sort.order, SortOrder::Desc);
+}
+
+/// Fails to delete anything whose name contains "locked".
+struct PickyCleaner;
+
+impl diskdoc::domain::ports::Cleaner for PickyCleaner {
+    fn delete_item(&self, path: &std::path::Path) -> anyhow::Result<u64> {
+        if path.to_string_lossy().contains("locked") {
+            anyhow::bail!("permission denied");
+        }
+        Ok(42)
+    }
+}
+
+#[test]
+fn test_mark_and_batch_delete() {
+    let mut app = App::new(
+        "/test".to_string(),
+        Box::new(PickyCleaner),
+        Box::new(MockAnalyzer::new()),
+    );
+    app.add_entry(file_entry("/test/dir/a", 100));
+    app.add_entry(file_entry("/test/dir/locked", 200));
+    app.add_entry(file_entry("/test/other", 50));
+    app.mode = AppMode::Browsing;
+
+    // Mark "dir" and both files inside it: nested marks count once.
+    app.toggle_mark();
+    app.enter_dir();
+    app.toggle_mark();
+    app.date_next();
+    app.toggle_mark();
+    app.go_up();
+    app.date_next();
+    app.toggle_mark();
+    assert_eq!(app.marked.len(), 4);
+    assert_eq!(app.marked_size(), 350);
+    assert_eq!(
+        app.marked_roots(),
+        vec![PathBuf::from("/test/dir"), PathBuf::from("/test/other")]
+    );
+
+    app.show_marked();
+    app.unmark_selected(); // "/test/dir" is first
+    assert_eq!(app.marked_size(), 350);
+
+    app.request_batch_delete();
+    assert_eq!(app.mode, AppMode::BatchDeleteConfirmation);
+    app.confirm_batch_delete();
+    assert_eq!(app.mode, AppMode::Marked);
+
+    let outcomes: Vec<(PathBuf, bool)> = app
+        .deletion_results
+        .iter()
+        .map(|r| (r.path.clone(), r.outcome.is_ok()))
+        .collect();
+    assert_eq!(
+        outcomes,
+        vec![
+            (PathBuf::from("/test/dir/a"), true),
+            (PathBuf::from("/test/dir/locked"), false),
+            (PathBuf::from("/test/other"), true),
+        ]
+    );
+    assert_eq!(
+        app.deletion_results[1].outcome,
+        Err("permission denied".to_string())
+    );
+
+    // Only the failure stays marked and in the tree.
+    assert_eq!(
+        app.marked.iter().collect::<Vec<_>>(),
+        vec![&PathBuf::from("/test/dir/locked")]
+    );
+    assert_eq!(app.total_size(), 200);
+}
+
+#[test]
+fn test_deleting_viewed_directory_moves_up() {
+    let mut app = setup_app();
+    app.add_entry(file_entry("/test/a/b/file", 100));
+    app.add_entry(file_entry("/test/keep", 10));
+    app.mode = AppMode::Browsing;
+
+    // Mark "a", then browse two levels below it and delete the marks.
+    app.toggle_mark();
+    app.enter_dir();
+    app.enter_dir();
+    assert_eq!(app.current_path, PathBuf::from("/test/a/b"));
+    app.request_batch_delete();
+    app.confirm_batch_delete();
+
+    assert_eq!(app.current_path, PathBuf::from("/test"));
+    assert_eq!(app.current_dir, app.tree.root());
+    assert_eq!(app.selection, 0);
+
+    // New entries may reuse the freed ids without showing up here.
+    app.add_entry(file_entry("/test/q/w", 5));
+    let names: Vec<PathBuf> = app
+        .get_current_files()
+        .into_iter()
+        .map(|f| f.path)
+        .collect();
+    assert_eq!(
+        names,
+        vec![PathBuf::from("/test/keep"), PathBuf::from("/test/q")]
+    );
+}
+
+#[test]
+fn test_imported_session_is_read_only() {
+    let mut app = setup_app();
+    app.import = Some(PathBuf::from("server.json"));
+    app.add_entry(file_entry("/test/a", 100));
+    app.toggle_mark();
+    app.mode = AppMode::Browsing;
+
+    app.request_delete();
+    assert_eq!(app.mode, AppMode::Browsing);
+    assert_eq!(
+        app.notice.as_deref(),
+        Some("Read-only: no deleting while browsing the import server.json")
+    );
+
+    // Confirming directly is refused too, and nothing leaves the tree.
+    app.item_to_delete = Some(PathBuf::from("/test/a"));
+    app.confirm_delete();
+    app.show_marked();
+    app.request_batch_delete();
+    assert_eq!(app.mode, AppMode::Marked);
+    app.confirm_batch_delete();
+    assert!(app.deletion_results.is_empty());
+    assert!(app.tree.find(std::path::Path::new("/test/a")).is_some());
+}
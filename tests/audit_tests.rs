@@ -0,0 +1,118 @@
+use diskdoc::application::app::{App, AppMode};
+use diskdoc::domain::entities::{
+    AuditEntry, FileType, Recommendation, RecommendationCategory, Removal,
+};
+use diskdoc::domain::ports::AuditLog;
+use diskdoc::infrastructure::audit::AuditStore;
+use diskdoc::interface::audit;
+use std::fs;
+use std::path::PathBuf;
+
+mod test_utils;
+use test_utils::{file_entry, typed_entry, MockAnalyzer, MockCleaner};
+
+#[test]
+fn test_audit_store_round_trip() {
+    let path = PathBuf::from("test_audit_round_trip.jsonl");
+    let _ = fs::remove_file(&path);
+    let store = AuditStore::with_path(&path);
+    assert!(store.records().unwrap().is_empty());
+
+    let deleted = AuditEntry {
+        target: "/data/app.log".to_string(),
+        size: Some(4096),
+        file_type: Some(FileType::Log),
+        recommendation: Some(RecommendationCategory::Log),
+        removal: Removal::Quarantine,
+        outcome: Ok(4000),
+    };
+    let failed = AuditEntry {
+        target: "/data/locked".to_string(),
+        size: None,
+        file_type: None,
+        recommendation: None,
+        removal: Removal::Permanent,
+        outcome: Err("Permission denied".to_string()),
+    };
+    store.append(deleted.clone()).unwrap();
+    store.append(failed.clone()).unwrap();
+
+    let records = store.records().unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].entry, deleted);
+    assert_eq!(records[1].entry, failed);
+    assert!(!records[0].user.is_empty());
+    assert!(records[0].time <= records[1].time);
+
+    // Newest first, with the trigger and outcome spelled out.
+    let mut out = Vec::new();
+    audit::print(&records, &mut out).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(lines[0].contains("browser") && lines[0].contains("failed: Permission denied"));
+    assert!(lines[1].contains("cleanup:log") && lines[1].contains("freed"));
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_app_audits_deletions_and_prunes() {
+    let path = PathBuf::from("test_audit_app.jsonl");
+    let _ = fs::remove_file(&path);
+
+    let mut analyzer = MockAnalyzer::new();
+    analyzer.recommendation = Some(Recommendation {
+        category: RecommendationCategory::Docker,
+        description: "Docker images".to_string(),
+        size: 2048,
+        path: None,
+        action_command: Some("docker system prune -f".to_string()),
+    });
+    let mut app = App::new(
+        "/test".to_string(),
+        Box::new(MockCleaner::new()),
+        Box::new(analyzer),
+    );
+    app.audit = Some(Box::new(AuditStore::with_path(&path)));
+    app.add_entry(file_entry("/test/big.bin", 500));
+    app.add_entry(typed_entry("/test/app.log", 300, FileType::Log));
+
+    // A deletion picked in the browser.
+    app.mode = AppMode::Browsing;
+    app.request_delete();
+    app.confirm_delete();
+
+    // The log and Docker cleanups from the dashboard.
+    app.scan_dashboard();
+    for category in [RecommendationCategory::Log, RecommendationCategory::Docker] {
+        app.recommendation_selection = app
+            .recommendations
+            .iter()
+            .position(|rec| rec.category == category)
+            .unwrap();
+        app.confirm_clean_recommendation();
+    }
+
+    app.show_audit();
+    assert_eq!(app.mode, AppMode::Audit);
+    let entries: Vec<&AuditEntry> = app.audit_records.iter().map(|r| &r.entry).collect();
+    assert_eq!(entries.len(), 3);
+    // Newest first.
+    let targets: Vec<&str> = entries.iter().rev().map(|e| e.target.as_str()).collect();
+    assert_eq!(
+        targets,
+        vec!["/test/big.bin", "/test/app.log", "docker system prune -f"]
+    );
+    let browser = entries[2];
+    assert_eq!(browser.size, Some(500));
+    assert_eq!(browser.file_type, Some(FileType::Normal));
+    assert_eq!(browser.recommendation, None);
+    assert_eq!(browser.outcome, Ok(100));
+    assert_eq!(entries[1].recommendation, Some(RecommendationCategory::Log));
+    assert_eq!(entries[1].file_type, Some(FileType::Log));
+    assert_eq!(entries[0].file_type, Some(FileType::Docker));
+    assert_eq!(entries[0].removal, Removal::Permanent);
+    assert_eq!(entries[0].outcome, Ok(2048));
+
+    fs::remove_file(&path).unwrap();
+}
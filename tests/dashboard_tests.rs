@@ -1,20 +1,8 @@
 use diskdoc::application::app::{App, AppMode};
-use diskdoc::domain::entities::{FileStats, FileType, Recommendation, RecommendationCategory};
+use diskdoc::domain::entities::{FileType, Recommendation, RecommendationCategory};
 mod test_utils;
 use std::path::PathBuf;
-use std::time::SystemTime;
-use test_utils::{MockAnalyzer, MockCleaner};
-
-fn create_file_stat(name: &str, size: u64, file_type: FileType) -> FileStats {
-    FileStats {
-        path: PathBuf::from(name),
-        size,
-        is_dir: false,
-        modified: SystemTime::UNIX_EPOCH,
-        children_count: None,
-        file_type,
-    }
-}
+use test_utils::{file_entry, typed_entry, MockAnalyzer, MockCleaner};
 
 #[test]
 fn test_dashboard_aggregation() {
@@ -25,14 +13,10 @@ fn test_dashboard_aggregation() {
     );
 
     // Add some files
-    app.files
-        .push(create_file_stat("app.log", 1000, FileType::Log));
-    app.files
-        .push(create_file_stat("error.log", 2000, FileType::Log));
-    app.files
-        .push(create_file_stat("cache.bin", 500, FileType::Cache));
-    app.files
-        .push(create_file_stat("normal.txt", 100, FileType::Normal));
+    app.add_entry(typed_entry("/test/app.log", 1000, FileType::Log));
+    app.add_entry(typed_entry("/test/error.log", 2000, FileType::Log));
+    app.add_entry(typed_entry("/test/cache.bin", 500, FileType::Cache));
+    app.add_entry(file_entry("/test/normal.txt", 100));
 
     // Scan dashboard
     app.scan_dashboard();
@@ -92,3 +76,59 @@ fn test_dashboard_cleanup_flow() {
     app.confirm_clean_recommendation();
     assert_eq!(app.mode, AppMode::Dashboard);
 }
+
+#[test]
+fn test_count_mode_flags_small_file_directories() {
+    use diskdoc::domain::entities::SizeMode;
+
+    let mut app = App::new(
+        "/test".to_string(),
+        Box::new(MockCleaner::new()),
+        Box::new(MockAnalyzer::new()),
+    );
+    app.small_files_threshold = 3;
+    for i in 0..4 {
+        let path = format!("/test/sessions/sess_{}", i);
+        app.add_entry(file_entry(&path, 100));
+    }
+    app.add_entry(file_entry("/test/big/a.iso", 1 << 30));
+    app.add_entry(typed_entry("/test/big/b.log", 200, FileType::Log));
+
+    app.toggle_count_mode();
+    assert_eq!(app.size_mode, SizeMode::Count);
+
+    // Entries, not bytes, drive the sort order.
+    let listed = app.get_current_files();
+    assert_eq!(listed[0].path, PathBuf::from("/test/sessions"));
+    assert_eq!(listed[0].size_for(SizeMode::Count), 5);
+
+    app.scan_dashboard();
+    let log_rec = app
+        .recommendations
+        .iter()
+        .find(|r| r.category == RecommendationCategory::Log)
+        .unwrap();
+    assert_eq!(log_rec.size, 1);
+
+    let small = app
+        .recommendations
+        .iter()
+        .find(|r| r.category == RecommendationCategory::SmallFiles)
+        .unwrap();
+    assert_eq!(small.size, 4);
+    assert_eq!(small.path, Some(PathBuf::from("/test/sessions")));
+
+    // Selecting it opens the directory instead of offering a cleanup.
+    app.mode = AppMode::Dashboard;
+    app.recommendation_selection = app
+        .recommendations
+        .iter()
+        .position(|r| r.category == RecommendationCategory::SmallFiles)
+        .unwrap();
+    app.request_clean_recommendation();
+    assert_eq!(app.mode, AppMode::Browsing);
+    assert_eq!(app.current_path, PathBuf::from("/test/sessions"));
+
+    app.toggle_count_mode();
+    assert_eq!(app.size_mode, SizeMode::Disk);
+}
@@ -0,0 +1,99 @@
+use diskdoc::application::app::{App, AppMode};
+use diskdoc::domain::diff::{DiffKind, TreeDiff};
+use diskdoc::domain::entities::SizeMode;
+use diskdoc::domain::tree::DirTree;
+use std::path::{Path, PathBuf};
+
+mod test_utils;
+use test_utils::{dir_entry, file_entry, MockAnalyzer, MockCleaner};
+
+fn build_tree(root: &str, entries: &[(&str, u64, bool)]) -> DirTree {
+    let mut tree = DirTree::new(root);
+    for &(path, size, is_dir) in entries {
+        tree.insert(if is_dir {
+            dir_entry(path, size)
+        } else {
+            file_entry(path, size)
+        });
+    }
+    tree
+}
+
+fn sample_diff() -> TreeDiff {
+    let old = build_tree(
+        "/old",
+        &[
+            ("/old/build", 0, true),
+            ("/old/build/out.o", 100, false),
+            ("/old/cache.db", 500, false),
+            ("/old/same.txt", 10, false),
+            ("/old/gone.log", 40, false),
+        ],
+    );
+    let new = build_tree(
+        "/new",
+        &[
+            ("/new/build", 0, true),
+            ("/new/build/out.o", 900, false),
+            ("/new/build/extra.o", 50, false),
+            ("/new/cache.db", 200, false),
+            ("/new/same.txt", 10, false),
+        ],
+    );
+    TreeDiff::new(old, new)
+}
+
+#[test]
+fn test_diff_classifies_changes() {
+    let diff = sample_diff();
+
+    let root = diff.entry(Path::new(""), SizeMode::Apparent);
+    assert_eq!(root.delta(), 1160 - 650);
+
+    let children = diff.children(Path::new(""), SizeMode::Apparent);
+    let kinds: Vec<(PathBuf, DiffKind, i64)> = children
+        .iter()
+        .map(|e| (e.path.clone(), e.kind(), e.delta()))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (PathBuf::from("build"), DiffKind::Grown, 850),
+            (PathBuf::from("cache.db"), DiffKind::Shrunk, -300),
+            (PathBuf::from("gone.log"), DiffKind::Removed, -40),
+        ]
+    );
+
+    let build = diff.children(Path::new("build"), SizeMode::Apparent);
+    assert_eq!(build.len(), 2);
+    assert!(build
+        .iter()
+        .any(|e| e.path == Path::new("build/extra.o") && e.kind() == DiffKind::Added));
+}
+
+#[test]
+fn test_diff_view_navigation() {
+    let mut app = App::new(
+        "/new".to_string(),
+        Box::new(MockCleaner::new()),
+        Box::new(MockAnalyzer::new()),
+    );
+    app.open_diff(sample_diff());
+    assert_eq!(app.mode, AppMode::Diff);
+
+    // Largest growth first by default; toggling puts the largest shrink first.
+    assert_eq!(app.diff_entries()[0].path, PathBuf::from("build"));
+    app.toggle_sort();
+    assert_eq!(app.diff_entries()[0].path, PathBuf::from("cache.db"));
+    app.toggle_sort();
+
+    app.diff_enter();
+    assert_eq!(app.diff_path, PathBuf::from("build"));
+    assert_eq!(app.current_path, PathBuf::from("/new/build"));
+    app.diff_next();
+    assert_eq!(app.diff_selection, 1);
+
+    app.diff_up();
+    assert_eq!(app.diff_path, PathBuf::new());
+    assert_eq!(app.diff_selection, 0);
+}
@@ -0,0 +1,102 @@
+use anyhow::Result;
+use diskdoc::application::app::App;
+use diskdoc::domain::entities::{
+    FileType, PlannedRemoval, Recommendation, RecommendationCategory, Removal, SizeMode,
+};
+use diskdoc::domain::ports::{Analyzer, AuditLog, Cleaner, DryRunLog};
+use diskdoc::infrastructure::audit::AuditStore;
+use diskdoc::infrastructure::dry_run::{DryRunAnalyzer, DryRunCleaner};
+use diskdoc::interface::report::Report;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+mod test_utils;
+use test_utils::{file_entry, typed_entry};
+
+/// Reports reclaimable Docker space and fails if it is ever really pruned.
+struct DockerStub;
+
+impl Analyzer for DockerStub {
+    fn analyze(&self) -> Result<Option<Recommendation>> {
+        Ok(Some(Recommendation {
+            category: RecommendationCategory::Docker,
+            description: "Docker images".to_string(),
+            size: 2048,
+            path: None,
+            action_command: Some("docker system prune -f".to_string()),
+        }))
+    }
+
+    fn prune(&self) -> Result<()> {
+        panic!("a dry run must not prune");
+    }
+}
+
+#[test]
+fn test_dry_run_cleaner_only_records() {
+    let path = Path::new("test_dry_run_file.txt");
+    fs::write(path, "content").unwrap();
+
+    let log = DryRunLog::new();
+    let cleaner = DryRunCleaner::new(log.clone());
+    assert_eq!(cleaner.removal(), Removal::DryRun);
+    assert_eq!(cleaner.delete_item(path).unwrap(), 7);
+    assert!(path.exists());
+    assert_eq!(
+        log.entries(),
+        vec![PlannedRemoval {
+            target: "test_dry_run_file.txt".to_string(),
+            allocated_size: fs::symlink_metadata(path).unwrap().blocks() * 512,
+            apparent_size: 7,
+            entries: 1,
+        }]
+    );
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_dry_run_cleanups_in_report() {
+    let dir = std::env::current_dir().unwrap().join("test_dry_run_dir");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("app.log"), "0123456789").unwrap();
+    fs::write(dir.join("keep.txt"), "abc").unwrap();
+
+    let log = DryRunLog::new();
+    let mut app = App::new(
+        dir.display().to_string(),
+        Box::new(DryRunCleaner::new(log.clone())),
+        Box::new(DryRunAnalyzer::new(Box::new(DockerStub), log.clone())),
+    );
+    app.dry_run = Some(log.clone());
+    let audit = dir.join("audit.jsonl");
+    app.audit = Some(Box::new(AuditStore::with_path(&audit)));
+    assert_eq!(app.analyzer.removal(), Removal::DryRun);
+    app.add_entry(typed_entry(dir.join("app.log"), 10, FileType::Log));
+    app.add_entry(file_entry(dir.join("keep.txt"), 3));
+    assert!(!app.is_simulated());
+
+    app.clean_all_recommendations();
+
+    assert!(dir.join("app.log").exists());
+    assert!(app.is_simulated());
+    let targets: Vec<String> = log.entries().into_iter().map(|r| r.target).collect();
+    assert!(targets.contains(&"docker system prune -f".to_string()));
+    assert!(targets.contains(&dir.join("app.log").display().to_string()));
+    assert_eq!(log.total(SizeMode::Apparent), 2048 + 10);
+    // Docker prunes free space but remove no entries of the tree.
+    assert_eq!(log.total(SizeMode::Count), 1);
+    // Nothing was removed, so nothing reaches the audit log.
+    assert!(AuditStore::with_path(&audit).records().unwrap().is_empty());
+
+    // The report measures removals like the rest of it.
+    app.size_mode = SizeMode::Apparent;
+    let report = Report::build(&mut app, 5);
+    let dry_run = report.dry_run.expect("dry runs are reported");
+    assert_eq!(dry_run.size, 2058);
+    assert_eq!(dry_run.removals.len(), 2);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,141 @@
+use diskdoc::application::app::{App, AppMode};
+use diskdoc::domain::entities::Filesystem;
+use diskdoc::domain::ports::{MountLister, ScanEvent, ScanHandle, Scanner};
+use diskdoc::infrastructure::mounts::parse_mounts;
+use diskdoc::interface::filesystems;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+
+mod test_utils;
+use test_utils::{file_entry, MockAnalyzer, MockCleaner};
+
+fn filesystem(mount_point: &str, total: u64, available: u64) -> Filesystem {
+    Filesystem {
+        device: "/dev/sda1".to_string(),
+        mount_point: PathBuf::from(mount_point),
+        fs_type: "ext4".to_string(),
+        options: vec!["rw".to_string(), "relatime".to_string()],
+        total,
+        available,
+        inodes_total: Some(1000),
+        inodes_free: Some(750),
+    }
+}
+
+struct StaticMounts(Vec<Filesystem>);
+
+impl MountLister for StaticMounts {
+    fn filesystems(&self) -> Vec<Filesystem> {
+        self.0.clone()
+    }
+}
+
+/// Reports a single file below the root and completes.
+struct OneFileScanner;
+
+impl Scanner for OneFileScanner {
+    fn scan(&self, root: &Path, tx: Sender<ScanEvent>) -> ScanHandle {
+        let _ = tx.send(ScanEvent::NewEntry(file_entry(root.join("file"), 100)));
+        let _ = tx.send(ScanEvent::Complete);
+        ScanHandle::new()
+    }
+}
+
+#[test]
+fn test_parse_mounts() {
+    let table = "\
+/dev/sda1 / ext4 rw,relatime 0 0
+proc /proc proc rw,nosuid,nodev 0 0
+/dev/sdb1 /mnt/my\\040disk vfat ro,noatime 0 0
+";
+    let mounts = parse_mounts(table);
+
+    assert_eq!(mounts.len(), 3);
+    assert_eq!(mounts[0].device, "/dev/sda1");
+    assert_eq!(mounts[0].options, vec!["rw", "relatime"]);
+    assert_eq!(mounts[2].mount_point, PathBuf::from("/mnt/my disk"));
+    assert_eq!(mounts[2].fs_type, "vfat");
+}
+
+#[test]
+fn test_filesystem_usage() {
+    let mut fs = filesystem("/", 1000, 250);
+    assert_eq!(fs.used(), 750);
+    assert!((fs.usage() - 0.75).abs() < 1e-9);
+    assert_eq!(fs.inode_usage(), Some(0.25));
+    assert!(!fs.is_read_only());
+
+    fs.total = 0;
+    fs.inodes_total = None;
+    fs.options = vec!["ro".to_string()];
+    assert_eq!(fs.usage(), 0.0);
+    assert_eq!(fs.inode_usage(), None);
+    assert!(fs.is_read_only());
+}
+
+#[test]
+fn test_pick_filesystem_to_scan() {
+    let mut app = App::new(
+        ".".to_string(),
+        Box::new(MockCleaner::new()),
+        Box::new(MockAnalyzer::new()),
+    );
+    app.scanner = Some(Box::new(OneFileScanner));
+    app.mounts = Some(Box::new(StaticMounts(vec![
+        filesystem("/", 1000, 500),
+        filesystem("/data", 2000, 100),
+    ])));
+    app.is_scanning = false;
+
+    app.show_filesystems();
+    assert_eq!(app.mode, AppMode::Filesystems);
+    assert_eq!(app.filesystems.len(), 2);
+
+    // Nothing scanned yet, so there is no result view to go back to.
+    app.close_filesystems();
+    assert_eq!(app.mode, AppMode::Filesystems);
+
+    app.filesystems_next();
+    app.scan_selected_filesystem();
+    assert_eq!(app.mode, AppMode::Scanning);
+    assert_eq!(app.tree.root_path(), Path::new("/data"));
+
+    app.wait_for_scan(|_| {});
+    assert_eq!(app.mode, AppMode::Browsing);
+    assert_eq!(app.total_size(), 100);
+
+    app.show_filesystems();
+    app.close_filesystems();
+    assert_eq!(app.mode, AppMode::Browsing);
+}
+
+#[test]
+fn test_imports_refuse_scanning_a_filesystem() {
+    let mut app = App::new(
+        "/srv".to_string(),
+        Box::new(MockCleaner::new()),
+        Box::new(MockAnalyzer::new()),
+    );
+    app.scanner = Some(Box::new(OneFileScanner));
+    app.mounts = Some(Box::new(StaticMounts(vec![filesystem("/data", 2000, 100)])));
+    app.import = Some(PathBuf::from("srv.json"));
+    app.is_scanning = false;
+
+    app.show_filesystems();
+    app.scan_selected_filesystem();
+    assert_eq!(app.mode, AppMode::Filesystems);
+    assert_eq!(app.tree.root_path(), Path::new("/srv"));
+    assert!(app.notice.as_deref().unwrap().starts_with("Read-only"));
+}
+
+#[test]
+fn test_print_filesystems() {
+    let mut out = Vec::new();
+    filesystems::print(&[filesystem("/data", 1000, 250)], &mut out).unwrap();
+    let text = String::from_utf8(out).unwrap();
+
+    assert!(text.starts_with("Mounted on"));
+    assert!(text.contains("/data"));
+    assert!(text.contains("75%"));
+    assert!(text.contains("rw,relatime"));
+}
@@ -0,0 +1,113 @@
+use diskdoc::domain::entities::FileType;
+use diskdoc::domain::history::{self, FilesystemSpace, HistoryRecord};
+use diskdoc::domain::tree::DirTree;
+use diskdoc::infrastructure::history::HistoryStore;
+use diskdoc::interface::trends;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+mod test_utils;
+use test_utils::{dir_entry, file_entry, typed_entry};
+
+const DAY: u64 = 86_400;
+
+fn record(day: u64, size: u64, available: Option<u64>) -> HistoryRecord {
+    HistoryRecord {
+        root: PathBuf::from("/data"),
+        time: UNIX_EPOCH + Duration::from_secs(day * DAY),
+        size,
+        entries: 3,
+        top_level: BTreeMap::from([("logs".to_string(), size)]),
+        file_types: BTreeMap::from([("log".to_string(), size)]),
+        filesystem: available.map(|available| FilesystemSpace {
+            total: 10_000,
+            available,
+        }),
+    }
+}
+
+#[test]
+fn test_record_from_tree() {
+    let mut tree = DirTree::new("/data");
+    tree.insert(dir_entry("/data/logs", 0));
+    tree.insert(typed_entry("/data/logs/app.log", 300, FileType::Log));
+    tree.insert(file_entry("/data/notes.txt", 100));
+
+    let record = HistoryRecord::from_tree(&tree, None);
+
+    assert_eq!(record.root, Path::new("/data"));
+    assert_eq!(record.size, 400);
+    assert_eq!(record.top_level.get("logs"), Some(&300));
+    assert_eq!(record.top_level.get("notes.txt"), Some(&100));
+    assert_eq!(record.file_types.get("log"), Some(&300));
+    assert_eq!(record.file_types.get("normal"), Some(&100));
+}
+
+#[test]
+fn test_trend_projects_time_to_full() {
+    let records = vec![
+        record(0, 1000, None),
+        record(1, 1100, None),
+        record(2, 1200, Some(1000)),
+    ];
+
+    let trend = history::trend(&records).unwrap();
+    assert!((trend.growth_per_day - 100.0).abs() < 1e-6);
+    assert_eq!(trend.time_to_full, Some(Duration::from_secs(10 * DAY)));
+}
+
+#[test]
+fn test_trend_far_future_is_no_projection() {
+    // A byte a day against this much free space overflows a Duration.
+    let records = vec![record(0, 1000, None), record(1, 1001, Some(u64::MAX))];
+
+    let trend = history::trend(&records).unwrap();
+    assert!(trend.growth_per_day > 0.0);
+    assert_eq!(trend.time_to_full, None);
+}
+
+#[test]
+fn test_trend_needs_spread_in_time() {
+    assert_eq!(history::trend(&[]), None);
+    assert_eq!(history::trend(&[record(3, 1000, None)]), None);
+
+    let shrinking = history::trend(&[record(0, 1000, Some(5)), record(1, 900, Some(5))]).unwrap();
+    assert!(shrinking.growth_per_day < 0.0);
+    assert_eq!(shrinking.time_to_full, None);
+}
+
+#[test]
+fn test_series_rows_fill_missing_entries() {
+    let mut later = record(1, 500, None);
+    later.top_level.insert("cache".to_string(), 700);
+    let rows = trends::top_level_rows(&[record(0, 400, None), later]);
+
+    assert_eq!(rows[0].name, "cache");
+    assert_eq!(rows[0].values, vec![0, 700]);
+    assert_eq!(rows[1].name, "logs");
+    assert_eq!(rows[1].delta(), 100);
+}
+
+#[test]
+fn test_history_store_appends_and_filters_by_root() {
+    let dir = PathBuf::from("test_history_store");
+    let store = HistoryStore::with_path(dir.join("history.jsonl"));
+
+    assert!(store.records_for(Path::new("/data")).unwrap().is_empty());
+
+    store.append(&record(2, 1200, Some(1000))).unwrap();
+    store.append(&record(0, 1000, None)).unwrap();
+    let mut other = record(1, 5, None);
+    other.root = PathBuf::from("/other");
+    store.append(&other).unwrap();
+
+    let records = store.records_for(Path::new("/data")).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(
+        records,
+        vec![record(0, 1000, None), record(2, 1200, Some(1000))]
+    );
+}
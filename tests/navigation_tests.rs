@@ -1,21 +1,13 @@
 use diskdoc::application::app::App;
-use diskdoc::domain::entities::{FileStats, FileType};
-use std::path::PathBuf;
-use std::time::SystemTime;
+use diskdoc::domain::entities::{FileStats, ScanError};
+use diskdoc::domain::ports::{ScanEvent, ScanHandle, Scanner};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
 
 mod test_utils;
-use test_utils::{MockAnalyzer, MockCleaner};
-
-fn create_entry(path: &str, is_dir: bool) -> FileStats {
-    FileStats {
-        path: PathBuf::from(path),
-        size: 100,
-        is_dir,
-        modified: SystemTime::UNIX_EPOCH,
-        children_count: None,
-        file_type: FileType::Normal,
-    }
-}
+use test_utils::{dir_entry, file_entry, MockAnalyzer, MockCleaner};
 
 #[test]
 fn test_navigation() {
@@ -30,9 +22,9 @@ fn test_navigation() {
     // /root/dir1
     // /root/dir1/file2
 
-    app.files.push(create_entry("/root/file1", false));
-    app.files.push(create_entry("/root/dir1", true));
-    app.files.push(create_entry("/root/dir1/file2", false));
+    app.add_entry(file_entry("/root/file1", 100));
+    app.add_entry(dir_entry("/root/dir1", 100));
+    app.add_entry(file_entry("/root/dir1/file2", 100));
 
     // Initial view should show file1 and dir1
     let current = app.get_current_files();
@@ -59,3 +51,166 @@ fn test_navigation() {
     assert_eq!(app.current_path, PathBuf::from("/root"));
     assert_eq!(app.selection, 0); // Logic resets to 0
 }
+
+/// Records the roots it was asked to scan and immediately completes.
+struct RecordingScanner {
+    roots: Arc<Mutex<Vec<PathBuf>>>,
+}
+
+impl Scanner for RecordingScanner {
+    fn scan(&self, root: &Path, tx: Sender<ScanEvent>) -> ScanHandle {
+        self.roots.lock().unwrap().push(root.to_path_buf());
+        let mut stats = file_entry(root.join("inner"), 100);
+        stats.size = 500;
+        let _ = tx.send(ScanEvent::NewEntry(stats));
+        let _ = tx.send(ScanEvent::Complete);
+        ScanHandle::new()
+    }
+}
+
+#[test]
+fn test_entering_unscanned_mount_point_scans_it() {
+    let mut app = App::new(
+        "/root".to_string(),
+        Box::new(MockCleaner::new()),
+        Box::new(MockAnalyzer::new()),
+    );
+    let roots = Arc::new(Mutex::new(Vec::new()));
+    app.scanner = Some(Box::new(RecordingScanner {
+        roots: roots.clone(),
+    }));
+    app.is_scanning = false;
+
+    let mut mount = dir_entry("/root/mnt", 100);
+    mount.is_mount_point = true;
+    app.add_entry(mount);
+
+    let listed = app.get_current_files();
+    assert!(listed[0].is_mount_point);
+
+    app.enter_dir();
+    assert_eq!(app.current_path, PathBuf::from("/root/mnt"));
+    assert_eq!(*roots.lock().unwrap(), vec![PathBuf::from("/root/mnt")]);
+
+    app.on_tick();
+    let current = app.get_current_files();
+    assert_eq!(current.len(), 1);
+    assert_eq!(current[0].path, PathBuf::from("/root/mnt/inner"));
+
+    // Once populated, entering again does not rescan.
+    app.go_up();
+    app.enter_dir();
+    assert_eq!(roots.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn test_rescan_replaces_subtree() {
+    let mut app = App::new(
+        "/root".to_string(),
+        Box::new(MockCleaner::new()),
+        Box::new(MockAnalyzer::new()),
+    );
+    let roots = Arc::new(Mutex::new(Vec::new()));
+    app.scanner = Some(Box::new(RecordingScanner {
+        roots: roots.clone(),
+    }));
+    app.is_scanning = false;
+
+    app.add_entry(dir_entry("/root/dir", 100));
+    app.add_entry(file_entry("/root/dir/stale", 100));
+    app.add_entry(file_entry("/root/other", 100));
+
+    let dir_index = app
+        .get_current_files()
+        .iter()
+        .position(|f| f.is_dir)
+        .unwrap();
+    app.selection = dir_index;
+    app.rescan_selected();
+    assert!(app.is_scanning);
+    assert_eq!(*roots.lock().unwrap(), vec![PathBuf::from("/root/dir")]);
+
+    app.on_tick();
+    assert!(!app.is_scanning);
+
+    // Only the rescanned subtree changed; siblings are untouched.
+    assert!(app.tree.find(&PathBuf::from("/root/dir/stale")).is_none());
+    assert!(app.tree.find(&PathBuf::from("/root/dir/inner")).is_some());
+    assert!(app.tree.find(&PathBuf::from("/root/other")).is_some());
+    let dir = app.tree.find(&PathBuf::from("/root/dir")).unwrap();
+    assert_eq!(app.tree.node(dir).apparent_size, 100 + 500);
+}
+
+/// Records the inodes it was told were counted and completes empty.
+struct CountingScanner {
+    counted: Arc<Mutex<HashSet<(u64, u64)>>>,
+}
+
+impl Scanner for CountingScanner {
+    fn scan(&self, root: &Path, tx: Sender<ScanEvent>) -> ScanHandle {
+        self.rescan(root, HashSet::new(), tx)
+    }
+
+    fn rescan(
+        &self,
+        _root: &Path,
+        counted: HashSet<(u64, u64)>,
+        tx: Sender<ScanEvent>,
+    ) -> ScanHandle {
+        *self.counted.lock().unwrap() = counted;
+        let _ = tx.send(ScanEvent::Complete);
+        ScanHandle::new()
+    }
+}
+
+fn hardlink(path: &str, inode: u64, duplicate_inode: bool) -> FileStats {
+    FileStats {
+        links: 2,
+        inode: Some((1, inode)),
+        duplicate_inode,
+        ..file_entry(path, 100)
+    }
+}
+
+#[test]
+fn test_rescan_forgets_old_errors_and_keeps_counted_inodes() {
+    let mut app = App::new(
+        "/root".to_string(),
+        Box::new(MockCleaner::new()),
+        Box::new(MockAnalyzer::new()),
+    );
+    let counted = Arc::new(Mutex::new(HashSet::new()));
+    app.scanner = Some(Box::new(CountingScanner {
+        counted: counted.clone(),
+    }));
+    app.is_scanning = false;
+
+    app.add_entry(dir_entry("/root/dir", 100));
+    app.add_entry(hardlink("/root/other/a", 7, false));
+    app.add_entry(hardlink("/root/dir/b", 7, true));
+    app.add_entry(hardlink("/root/dir/c", 9, false));
+    app.record_error(ScanError::other(
+        Some(PathBuf::from("/root/dir/locked")),
+        "denied",
+    ));
+    app.record_error(ScanError::other(
+        Some(PathBuf::from("/root/other/x")),
+        "denied",
+    ));
+
+    app.selection = app
+        .get_current_files()
+        .iter()
+        .position(|f| f.path == Path::new("/root/dir"))
+        .unwrap();
+    app.rescan_selected();
+    app.on_tick();
+
+    // Only the inode counted outside the rescanned directory is seeded.
+    assert_eq!(counted.lock().unwrap().clone(), HashSet::from([(1, 7)]));
+    assert_eq!(app.errors.len(), 1);
+    assert_eq!(app.errors[0].path, Some(PathBuf::from("/root/other/x")));
+    let dir = app.tree.find(Path::new("/root/dir")).unwrap();
+    assert!(!app.tree.node(dir).incomplete);
+    assert!(app.tree.node(app.tree.root()).incomplete);
+}
@@ -0,0 +1,156 @@
+use diskdoc::domain::entities::{FileStats, FileType};
+use diskdoc::domain::ports::{ScanEvent, Scanner};
+use diskdoc::domain::tree::DirTree;
+use diskdoc::infrastructure::ncdu::{self, NcduScanner};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::SystemTime;
+
+mod test_utils;
+use test_utils::file_entry;
+
+const EXPORT: &str = r#"[1,2,{"progname":"ncdu","progver":"1.19","timestamp":1700000000},
+[{"name":"/srv","asize":4096,"dsize":4096,"dev":2049},
+  {"name":"app.log","asize":1000,"dsize":4096},
+  [{"name":"data","asize":4096,"dsize":4096},
+    {"name":"a.bin","asize":500,"dsize":512,"ino":42,"hlnkc":true},
+    {"name":"b.bin","asize":500,"dsize":512,"ino":42,"hlnkc":true}],
+  [{"name":"locked","asize":4096,"dsize":4096,"read_error":true}],
+  [{"name":"mnt","asize":4096,"dsize":4096,"dev":2050}],
+  {"name":"skipped","excluded":"pattern"}]]"#;
+
+fn replay(scanner: &NcduScanner, root: &Path) -> (Vec<FileStats>, usize, usize) {
+    let (tx, rx) = mpsc::channel();
+    let _handle = scanner.scan(root, tx);
+    let mut entries = Vec::new();
+    let mut errors = 0;
+    let mut excluded = 0;
+    for event in rx {
+        match event {
+            ScanEvent::NewEntry(stats) => entries.push(stats),
+            ScanEvent::Error(_) => errors += 1,
+            ScanEvent::Progress(progress) => excluded = progress.excluded,
+            ScanEvent::Complete => break,
+        }
+    }
+    (entries, errors, excluded)
+}
+
+#[test]
+fn test_import_ncdu_export() {
+    let scanner = NcduScanner::from_reader(EXPORT.as_bytes()).unwrap();
+    assert_eq!(scanner.root(), Path::new("/srv"));
+
+    let (entries, errors, excluded) = replay(&scanner, Path::new("/srv"));
+    let mut tree = DirTree::new("/srv");
+    for stats in &entries {
+        tree.insert(stats.clone());
+    }
+
+    assert_eq!(tree.len(), 6);
+    assert_eq!(errors, 1);
+    assert_eq!(excluded, 1);
+
+    let log = tree.find(Path::new("/srv/app.log")).unwrap();
+    assert_eq!(tree.node(log).file_type, FileType::Log);
+
+    // The second hard link is not counted again.
+    let data = tree.find(Path::new("/srv/data")).unwrap();
+    assert_eq!(tree.node(data).allocated_size, 4096 + 512);
+
+    let mnt = tree.find(Path::new("/srv/mnt")).unwrap();
+    assert!(tree.node(mnt).is_mount_point);
+    assert!(!tree.node(data).is_mount_point);
+}
+
+#[test]
+fn test_build_tree_matches_replay() {
+    let scanner = NcduScanner::from_reader(EXPORT.as_bytes()).unwrap();
+    let (tree, errors) = scanner.build_tree();
+
+    assert_eq!(tree.len(), 6);
+    assert_eq!(errors.len(), 1);
+    let locked = tree.find(Path::new("/srv/locked")).unwrap();
+    assert!(tree.node(locked).incomplete);
+    assert!(tree.node(tree.root()).incomplete);
+
+    let data = tree.find(Path::new("/srv/data")).unwrap();
+    assert_eq!(tree.node(data).allocated_size, 4096 + 512);
+    let mnt = tree.find(Path::new("/srv/mnt")).unwrap();
+    assert!(tree.node(mnt).is_mount_point);
+}
+
+#[test]
+fn test_import_subtree_only() {
+    let scanner = NcduScanner::from_reader(EXPORT.as_bytes()).unwrap();
+    let (entries, _, _) = replay(&scanner, Path::new("/srv/data"));
+    let paths: Vec<PathBuf> = entries.into_iter().map(|e| e.path).collect();
+    assert_eq!(
+        paths,
+        vec![
+            PathBuf::from("/srv/data"),
+            PathBuf::from("/srv/data/a.bin"),
+            PathBuf::from("/srv/data/b.bin"),
+        ]
+    );
+}
+
+#[test]
+fn test_reject_non_ncdu_files() {
+    assert!(NcduScanner::from_reader(r#"{"version":1}"#.as_bytes()).is_err());
+    assert!(NcduScanner::from_reader("[2,0,{}]".as_bytes()).is_err());
+}
+
+#[test]
+fn test_export_round_trip() {
+    let scanner = NcduScanner::from_reader(EXPORT.as_bytes()).unwrap();
+    let (entries, _, _) = replay(&scanner, Path::new("/srv"));
+    let mut tree = DirTree::new("/srv");
+    for stats in entries {
+        tree.insert(stats);
+    }
+
+    let mut out = Vec::new();
+    ncdu::export(&tree, &[], &mut out).unwrap();
+
+    let reimported = NcduScanner::from_reader(out.as_slice()).unwrap();
+    let (entries, _, _) = replay(&reimported, Path::new("/srv"));
+    let mut copy = DirTree::new("/srv");
+    for stats in entries {
+        copy.insert(stats);
+    }
+
+    assert_eq!(copy.len(), tree.len());
+    let root = copy.node(copy.root());
+    assert_eq!(root.allocated_size, tree.node(tree.root()).allocated_size);
+    assert_eq!(root.apparent_size, tree.node(tree.root()).apparent_size);
+    let mnt = copy.find(Path::new("/srv/mnt")).unwrap();
+    assert!(copy.node(mnt).is_mount_point);
+    let b = copy.find(Path::new("/srv/data/b.bin")).unwrap();
+    assert!(copy.node(b).duplicate_inode);
+    assert_eq!(copy.node(b).modified, SystemTime::UNIX_EPOCH);
+}
+
+#[test]
+fn test_export_round_trip_deep_tree() {
+    // Far deeper than serde_json's default recursion limit of 128.
+    let mut deepest = PathBuf::from("/deep");
+    for level in 0..500 {
+        deepest.push(format!("d{}", level));
+    }
+    let mut tree = DirTree::new("/deep");
+    tree.insert(file_entry(deepest.join("leaf"), 100));
+
+    let mut out = Vec::new();
+    ncdu::export(&tree, &[], &mut out).unwrap();
+
+    let reimported = NcduScanner::from_reader(out.as_slice()).unwrap();
+    let (entries, _, _) = replay(&reimported, Path::new("/deep"));
+    let mut copy = DirTree::new("/deep");
+    for stats in entries {
+        copy.insert(stats);
+    }
+    assert_eq!(copy.len(), tree.len());
+    let leaf = copy.find(&deepest.join("leaf")).unwrap();
+    assert_eq!(copy.node(leaf).apparent_size, 100);
+}
@@ -0,0 +1,272 @@
+use diskdoc::application::app::App;
+use diskdoc::domain::entities::Removal;
+use diskdoc::domain::ports::{AuditLog, Cleaner};
+use diskdoc::infrastructure::audit::AuditStore;
+use diskdoc::infrastructure::docker::DockerAnalyzerImpl;
+use diskdoc::infrastructure::quarantine::{PurgePolicy, QuarantineCleaner};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+mod test_utils;
+use test_utils::file_entry;
+
+/// A scratch directory under the working directory, so the quarantine is
+/// on the same filesystem as the files deleted into it.
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::current_dir().unwrap().join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("data")).unwrap();
+    dir
+}
+
+fn cleaner(dir: &Path, policy: PurgePolicy) -> QuarantineCleaner {
+    QuarantineCleaner::with_paths(dir.join("quarantine"), dir.join("quarantine.jsonl"), policy)
+}
+
+#[test]
+fn test_quarantine_and_undo() {
+    let dir = scratch("test_quarantine_undo");
+    let data = dir.join("data");
+    fs::create_dir_all(data.join("build")).unwrap();
+    fs::write(data.join("build/out.o"), "12345").unwrap();
+    fs::write(data.join("a.log"), "first").unwrap();
+
+    let cleaner = cleaner(&dir, PurgePolicy::default());
+    assert_eq!(cleaner.removal(), Removal::Quarantine);
+    cleaner.delete_item(&data.join("a.log")).unwrap();
+    fs::write(data.join("a.log"), "second").unwrap();
+    cleaner.delete_item(&data.join("a.log")).unwrap();
+    let size = cleaner.delete_item(&data.join("build")).unwrap();
+    assert!(size >= 5, "directory size includes its contents");
+
+    assert!(!data.join("a.log").exists());
+    assert!(!data.join("build").exists());
+    let entries = cleaner.entries().unwrap();
+    assert_eq!(entries.len(), 3);
+    assert!(entries.iter().all(|e| e.stored.exists()));
+    assert!(entries[0].stored.starts_with(dir.join("quarantine")));
+
+    // Newest first: the directory, then the second a.log.
+    assert_eq!(
+        cleaner.undo(2).unwrap(),
+        vec![data.join("build"), data.join("a.log")]
+    );
+    assert_eq!(
+        fs::read_to_string(data.join("build/out.o")).unwrap(),
+        "12345"
+    );
+    assert_eq!(fs::read_to_string(data.join("a.log")).unwrap(), "second");
+
+    // The first a.log cannot go back over the restored one.
+    assert!(cleaner.undo(1).is_err());
+    assert_eq!(cleaner.entries().unwrap().len(), 1);
+    fs::remove_file(data.join("a.log")).unwrap();
+    assert_eq!(cleaner.undo(5).unwrap(), vec![data.join("a.log")]);
+    assert_eq!(fs::read_to_string(data.join("a.log")).unwrap(), "first");
+    assert!(cleaner.undo(1).unwrap().is_empty());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_unjournaled_item_is_put_back() {
+    let dir = scratch("test_quarantine_no_journal");
+    let data = dir.join("data");
+    fs::write(data.join("a.log"), "first").unwrap();
+    // A directory where the journal should be cannot be appended to.
+    fs::create_dir_all(dir.join("quarantine.jsonl")).unwrap();
+
+    let cleaner = cleaner(&dir, PurgePolicy::default());
+    assert!(cleaner.delete_item(&data.join("a.log")).is_err());
+    assert_eq!(fs::read_to_string(data.join("a.log")).unwrap(), "first");
+    assert_eq!(fs::read_dir(dir.join("quarantine")).unwrap().count(), 0);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_undo_restores_whole_operations() {
+    let dir = scratch("test_quarantine_operations");
+    let data = dir.join("data");
+    for name in ["a", "b", "c"] {
+        fs::write(data.join(name), name).unwrap();
+    }
+
+    let session = cleaner(&dir, PurgePolicy::default());
+    session.begin_operation();
+    session.delete_item(&data.join("a")).unwrap();
+    session.delete_item(&data.join("b")).unwrap();
+    session.begin_operation();
+    session.delete_item(&data.join("c")).unwrap();
+
+    // A later session still knows which items went together.
+    let restarted = cleaner(&dir, PurgePolicy::default());
+    // Previewing leaves the journal and the files where they are.
+    let pending: Vec<PathBuf> = restarted
+        .pending_undo(2)
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.original)
+        .collect();
+    assert_eq!(
+        pending,
+        vec![data.join("c"), data.join("b"), data.join("a")]
+    );
+    assert_eq!(restarted.entries().unwrap().len(), 3);
+    assert!(!data.join("c").exists());
+
+    assert_eq!(restarted.undo(1).unwrap(), vec![data.join("c")]);
+    assert_eq!(
+        restarted.undo(1).unwrap(),
+        vec![data.join("b"), data.join("a")]
+    );
+    assert!(restarted.entries().unwrap().is_empty());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_purge_spares_the_operation_in_progress() {
+    let dir = scratch("test_quarantine_spare");
+    let data = dir.join("data");
+    for name in ["a", "b", "c"] {
+        fs::write(data.join(name), "abcd").unwrap();
+    }
+    fs::write(data.join("big"), "0123456789").unwrap();
+
+    let policy = PurgePolicy {
+        max_age: None,
+        max_size: Some(8),
+    };
+    let cleaner = cleaner(&dir, policy);
+    // Twelve bytes in one batch stay undoable despite the budget of eight.
+    cleaner.begin_operation();
+    for name in ["a", "b", "c"] {
+        cleaner.delete_item(&data.join(name)).unwrap();
+    }
+    assert_eq!(cleaner.entries().unwrap().len(), 3);
+
+    // An item over the whole budget is refused rather than purged at once.
+    let err = cleaner.delete_item(&data.join("big")).unwrap_err();
+    assert!(err.to_string().contains("over the quarantine limit"));
+    assert!(data.join("big").exists());
+
+    assert_eq!(cleaner.undo(1).unwrap().len(), 3);
+    assert!(data.join("a").exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_quarantine_purges_by_age_and_size() {
+    let dir = scratch("test_quarantine_purge");
+    let data = dir.join("data");
+    for (name, contents) in [("old", "aaaa"), ("mid", "bbbb"), ("new", "cccc")] {
+        fs::write(data.join(name), contents).unwrap();
+    }
+
+    let policy = PurgePolicy {
+        max_age: Some(Duration::from_secs(86_400)),
+        max_size: Some(8),
+    };
+    let cleaner = cleaner(&dir, policy);
+    cleaner.delete_item(&data.join("old")).unwrap();
+    cleaner.delete_item(&data.join("mid")).unwrap();
+    // Twelve bytes are over the budget of eight, so the oldest item goes.
+    cleaner.delete_item(&data.join("new")).unwrap();
+    let entries = cleaner.entries().unwrap();
+    let originals: Vec<&PathBuf> = entries.iter().map(|e| &e.original).collect();
+    assert_eq!(originals, vec![&data.join("mid"), &data.join("new")]);
+
+    // Two days later everything has expired.
+    let later = SystemTime::now() + Duration::from_secs(2 * 86_400);
+    let purged = cleaner.purge_at(later).unwrap();
+    assert_eq!(purged.len(), 2);
+    assert!(purged.iter().all(|e| !e.stored.exists()));
+    assert!(cleaner.entries().unwrap().is_empty());
+    assert_eq!(fs::read_dir(dir.join("quarantine")).unwrap().count(), 0);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_purge_goes_on_past_failures_and_is_audited() {
+    let dir = scratch("test_quarantine_purge_failure");
+    let data = dir.join("data");
+    for name in ["stuck", "gone"] {
+        fs::write(data.join(name), "abcd").unwrap();
+    }
+
+    let policy = PurgePolicy {
+        max_age: Some(Duration::from_secs(86_400)),
+        max_size: None,
+    };
+    let audit = AuditStore::with_path(dir.join("audit.jsonl"));
+    let cleaner = cleaner(&dir, policy).with_audit(Box::new(AuditStore::with_path(audit.path())));
+    cleaner.delete_item(&data.join("stuck")).unwrap();
+    cleaner.delete_item(&data.join("gone")).unwrap();
+
+    // Point the older entry below a regular file, where it cannot be removed.
+    fs::write(data.join("plain"), "").unwrap();
+    let mut entries = cleaner.entries().unwrap();
+    entries[0].stored = data.join("plain").join("stuck");
+    let journal: String = entries
+        .iter()
+        .map(|e| serde_json::to_string(e).unwrap() + "\n")
+        .collect();
+    fs::write(dir.join("quarantine.jsonl"), journal).unwrap();
+
+    let later = SystemTime::now() + Duration::from_secs(2 * 86_400);
+    let err = cleaner.purge_at(later).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("1 quarantined items could not be purged"));
+    let left = cleaner.entries().unwrap();
+    assert_eq!(left.len(), 1);
+    assert_eq!(left[0].original, data.join("stuck"));
+    assert!(!entries[1].stored.exists());
+
+    let records = audit.records().unwrap();
+    assert_eq!(records.len(), 2);
+    assert!(records.iter().all(|r| r.entry.removal == Removal::Purge));
+    assert!(records[0].entry.outcome.is_err());
+    assert_eq!(records[1].entry.outcome, Ok(4));
+    assert_eq!(
+        records[1].entry.target,
+        data.join("gone").display().to_string()
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_app_undoes_last_batch() {
+    let dir = scratch("test_quarantine_app");
+    let data = dir.join("data");
+    fs::write(data.join("one"), "1").unwrap();
+    fs::write(data.join("two"), "22").unwrap();
+
+    let mut app = App::new(
+        data.display().to_string(),
+        Box::new(cleaner(&dir, PurgePolicy::default())),
+        Box::new(DockerAnalyzerImpl::new()),
+    );
+    app.add_entry(file_entry(data.join("one"), 1));
+    app.add_entry(file_entry(data.join("two"), 2));
+    app.marked.insert(data.join("one"));
+    app.marked.insert(data.join("two"));
+    app.confirm_batch_delete();
+    assert_eq!(app.last_removed, 2);
+    assert!(!data.join("one").exists());
+
+    app.undo_last();
+    assert_eq!(app.notice.as_deref(), Some("Restored 2 item(s)"));
+    assert!(data.join("one").exists());
+    assert!(data.join("two").exists());
+
+    app.undo_last();
+    assert_eq!(app.notice.as_deref(), Some("Nothing to undo"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,86 @@
+use diskdoc::application::app::App;
+use diskdoc::domain::entities::{FileType, ScanError, ScanProgress};
+use diskdoc::domain::ports::ScanEvent;
+use diskdoc::interface::report::{Report, StreamRecord, REPORT_VERSION};
+use std::path::PathBuf;
+
+mod test_utils;
+use test_utils::{file_entry, typed_entry, MockAnalyzer, MockCleaner};
+
+fn setup_app() -> App {
+    App::new(
+        "/test".to_string(),
+        Box::new(MockCleaner::new()),
+        Box::new(MockAnalyzer::new()),
+    )
+}
+
+#[test]
+fn test_report_summarizes_scan() {
+    let mut app = setup_app();
+    app.add_entry(typed_entry("/test/app/debug.log", 300, FileType::Log));
+    app.add_entry(file_entry("/test/app/data.bin", 1000));
+    app.add_entry(file_entry("/test/small.txt", 10));
+    app.record_error(ScanError::other(
+        Some(PathBuf::from("/test/app/locked")),
+        "denied",
+    ));
+
+    let report = Report::build(&mut app, 1);
+
+    assert_eq!(report.version, REPORT_VERSION);
+    assert_eq!(report.summary.size, 1310);
+    assert_eq!(report.summary.files, 3);
+    assert_eq!(report.summary.directories, 1);
+    assert_eq!(report.summary.errors, 1);
+    assert!(report.summary.incomplete);
+
+    assert_eq!(report.top_entries.len(), 1);
+    assert_eq!(report.top_entries[0].path, "/test/app");
+    assert!(report.top_entries[0].incomplete);
+    assert_eq!(report.largest_files[0].path, "/test/app/data.bin");
+
+    assert_eq!(report.file_types[0].file_type, "normal");
+    assert_eq!(report.file_types[0].size, 1010);
+    assert_eq!(report.file_types[1].file_type, "log");
+    assert_eq!(report.recommendations[0].category, "log");
+
+    let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["errors"][0]["kind"], "other");
+    assert_eq!(json["summary"]["allocated_size"], 1310);
+}
+
+#[test]
+fn test_stream_records_are_tagged() {
+    let entry = ScanEvent::NewEntry(file_entry("/test/a", 5));
+    let record = StreamRecord::from_event(&entry).unwrap();
+    let json = serde_json::to_value(&record).unwrap();
+    assert_eq!(json["type"], "entry");
+    assert_eq!(json["path"], "/test/a");
+
+    let progress = ScanEvent::Progress(ScanProgress::default());
+    let json = serde_json::to_value(StreamRecord::from_event(&progress).unwrap()).unwrap();
+    assert_eq!(json["type"], "progress");
+
+    assert!(StreamRecord::from_event(&ScanEvent::Complete).is_none());
+}
+
+#[test]
+fn test_report_lists_follow_sort_spec() {
+    use diskdoc::domain::entities::{SortKey, SortSpec};
+
+    let mut app = setup_app();
+    app.add_entry(file_entry("/test/zeta.bin", 500));
+    app.add_entry(file_entry("/test/alpha.bin", 300));
+    app.add_entry(file_entry("/test/tiny.bin", 1));
+    app.sort = SortSpec::new(SortKey::Name);
+
+    let report = Report::build(&mut app, 2);
+
+    // The two largest entries, listed by name.
+    let paths: Vec<&str> = report.top_entries.iter().map(|e| e.path.as_str()).collect();
+    assert_eq!(paths, vec!["/test/alpha.bin", "/test/zeta.bin"]);
+    assert_eq!(report.sort.key, "name");
+    assert_eq!(report.sort.order, "asc");
+    assert!(!report.sort.dirs_first);
+}
@@ -0,0 +1,130 @@
+use diskdoc::application::app::{App, AppMode};
+use diskdoc::domain::entities::FileType;
+use diskdoc::domain::safety::{Blocked, SafetyRules};
+use std::path::{Path, PathBuf};
+
+mod test_utils;
+use test_utils::{file_entry, typed_entry, MockAnalyzer, MockCleaner};
+
+fn setup_app() -> App {
+    App::new(
+        "/test".to_string(),
+        Box::new(MockCleaner::new()),
+        Box::new(MockAnalyzer::new()),
+    )
+}
+
+#[test]
+fn test_safety_check() {
+    let mut rules = SafetyRules::default();
+    rules.protected.push(PathBuf::from("/srv/data/keep"));
+    let root = Path::new("/srv/data");
+
+    assert_eq!(rules.check(root, Path::new("/srv/data/build")), Ok(()));
+    assert_eq!(rules.check(root, root), Err(Blocked::ScanRoot));
+    assert_eq!(
+        rules.check(root, Path::new("/srv/other")),
+        Err(Blocked::OutsideRoot(root.to_path_buf()))
+    );
+    assert_eq!(
+        rules.check(root, Path::new("/srv/data/../other")),
+        Err(Blocked::OutsideRoot(root.to_path_buf()))
+    );
+    // Protected paths block their parents and children alike.
+    assert_eq!(
+        rules.check(root, Path::new("/srv/data/keep/old")),
+        Err(Blocked::Protected(PathBuf::from("/srv/data/keep")))
+    );
+    assert!(rules.check(root, Path::new("/srv/data/keep")).is_err());
+    assert_eq!(
+        rules.check(root, Path::new("/srv/data/repo/.git/objects")),
+        Err(Blocked::Protected(PathBuf::from("/srv/data/repo/.git")))
+    );
+
+    // System directories may be emptied but not removed.
+    let root = Path::new("/");
+    assert_eq!(
+        rules.check(root, Path::new("/usr")),
+        Err(Blocked::Keeps(PathBuf::from("/usr")))
+    );
+    assert_eq!(rules.check(root, Path::new("/var/cache/apt")), Ok(()));
+    assert_eq!(
+        rules
+            .check(root, Path::new("/var"))
+            .unwrap_err()
+            .to_string(),
+        "refusing to delete /var, a system or home directory"
+    );
+}
+
+#[test]
+fn test_safety_limits() {
+    let rules = SafetyRules {
+        max_bytes: 1000,
+        max_items: 2,
+        ..SafetyRules::default()
+    };
+    assert!(!rules.exceeds_limits(1000, 2));
+    assert!(rules.exceeds_limits(1001, 1));
+    assert!(rules.exceeds_limits(10, 3));
+}
+
+#[test]
+fn test_blocked_delete_leaves_entry() {
+    let mut app = setup_app();
+    app.add_entry(file_entry("/test/repo/.git/pack", 500));
+    app.add_entry(file_entry("/test/big", 100));
+    app.mode = AppMode::Browsing;
+
+    // "repo" holds a .git directory, so it is refused before confirming.
+    app.request_delete();
+    assert_eq!(app.mode, AppMode::Browsing);
+    assert!(app.item_to_delete.is_none());
+    assert_eq!(
+        app.notice.as_deref(),
+        Some("/test/repo not deleted: refusing to delete protected path /test/repo/.git")
+    );
+    assert!(app.tree.find(Path::new("/test/repo")).is_some());
+
+    app.date_next();
+    app.request_delete();
+    assert_eq!(app.mode, AppMode::DeleteConfirmation);
+    app.confirm_delete();
+    assert!(app.tree.find(Path::new("/test/big")).is_none());
+}
+
+#[test]
+fn test_delete_over_limit_needs_second_confirmation() {
+    let mut app = setup_app();
+    app.safety.max_bytes = 50;
+    app.add_entry(file_entry("/test/big", 100));
+    app.mode = AppMode::Browsing;
+
+    app.request_delete();
+    app.confirm_delete();
+    assert_eq!(app.mode, AppMode::DeleteConfirmation);
+    assert_eq!(app.over_limit, Some((100, 1)));
+    assert!(app.tree.find(Path::new("/test/big")).is_some());
+
+    // Cancelling forgets the warning; confirming twice goes ahead.
+    app.cancel_delete();
+    assert!(app.over_limit.is_none());
+    app.request_delete();
+    app.confirm_delete();
+    app.confirm_delete();
+    assert_eq!(app.mode, AppMode::Browsing);
+    assert!(app.over_limit.is_none());
+    assert!(app.tree.find(Path::new("/test/big")).is_none());
+}
+
+#[test]
+fn test_clean_all_only_in_dry_runs() {
+    let mut app = setup_app();
+    app.safety.max_bytes = 50;
+    app.add_entry(typed_entry("/test/app.log", 100, FileType::Log));
+
+    // Outside a dry run nothing is cleaned, limits or not.
+    app.clean_all_recommendations();
+    assert!(app.tree.find(Path::new("/test/app.log")).is_some());
+    assert!(app.over_limit.is_none());
+}
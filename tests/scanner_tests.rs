@@ -8,11 +8,191 @@ fn test_file_stats_creation() {
     let stats = FileStats {
         path: path.clone(),
         size: 1024,
+        allocated_size: 4096,
         is_dir: false,
         modified: SystemTime::UNIX_EPOCH,
         children_count: None,
         file_type: FileType::Normal,
+        links: 1,
+        duplicate_inode: false,
+        is_mount_point: false,
+        inode: None,
     };
 
     assert_eq!(stats.path, PathBuf::from("test_file.txt"));
 }
+
+#[test]
+fn test_scan_progress_estimates() {
+    use diskdoc::domain::entities::ScanProgress;
+    use std::time::Duration;
+
+    let progress = ScanProgress {
+        bytes_seen: 250,
+        elapsed: Duration::from_secs(5),
+        expected_bytes: Some(1000),
+        ..ScanProgress::default()
+    };
+    assert_eq!(progress.percent(), Some(25));
+    assert_eq!(progress.throughput(), 50.0);
+    assert_eq!(progress.eta(), Some(Duration::from_secs(15)));
+
+    let unknown = ScanProgress::default();
+    assert_eq!(unknown.percent(), None);
+    assert_eq!(unknown.eta(), None);
+}
+
+#[test]
+fn test_scanner_reports_progress() {
+    use diskdoc::domain::ports::{ScanEvent, Scanner};
+    use diskdoc::infrastructure::scanner::FsScanner;
+    use std::fs;
+
+    let dir = PathBuf::from("test_scan_progress");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("a.txt"), "hello").unwrap();
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    FsScanner::new().scan(&dir, tx);
+    let events: Vec<ScanEvent> = rx.iter().collect();
+    fs::remove_dir_all(&dir).unwrap();
+
+    let last_progress = events
+        .iter()
+        .rev()
+        .find_map(|e| match e {
+            ScanEvent::Progress(p) => Some(p.clone()),
+            _ => None,
+        })
+        .expect("no progress event");
+    assert_eq!(last_progress.entries_seen, 2);
+    assert!(matches!(events.last(), Some(ScanEvent::Complete)));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_scanner_counts_hardlinks_once() {
+    use diskdoc::domain::ports::{ScanEvent, Scanner};
+    use diskdoc::infrastructure::scanner::FsScanner;
+    use std::fs;
+
+    let dir = PathBuf::from("test_scan_hardlinks");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("original"), vec![0u8; 10_000]).unwrap();
+    fs::hard_link(dir.join("original"), dir.join("link")).unwrap();
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    FsScanner::new().scan(&dir, tx);
+    let files: Vec<FileStats> = rx
+        .iter()
+        .filter_map(|e| match e {
+            ScanEvent::NewEntry(stats) if !stats.is_dir => Some(stats),
+            _ => None,
+        })
+        .collect();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(files.len(), 2);
+    assert!(files.iter().all(|f| f.is_hardlink() && f.links == 2));
+    assert_eq!(files.iter().filter(|f| f.duplicate_inode).count(), 1);
+}
+
+#[test]
+fn test_scanner_excludes_and_ignore_files() {
+    use diskdoc::domain::entities::ScanOptions;
+    use diskdoc::domain::ports::{ScanEvent, Scanner};
+    use diskdoc::infrastructure::scanner::FsScanner;
+    use std::fs;
+
+    let dir = PathBuf::from("test_scan_excludes");
+    fs::create_dir_all(dir.join("node_modules/pkg")).unwrap();
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("node_modules/pkg/index.js"), "x").unwrap();
+    fs::write(dir.join("src/main.rs"), "x").unwrap();
+    fs::write(dir.join("src/debug.log"), "x").unwrap();
+    fs::write(dir.join(".diskdocignore"), "*.log\n").unwrap();
+
+    let scanner = FsScanner::with_options(ScanOptions {
+        excludes: vec!["node_modules".to_string()],
+        use_ignore_files: true,
+        ..ScanOptions::default()
+    });
+    let (tx, rx) = std::sync::mpsc::channel();
+    scanner.scan(&dir, tx);
+    let events: Vec<ScanEvent> = rx.iter().collect();
+    fs::remove_dir_all(&dir).unwrap();
+
+    let paths: Vec<PathBuf> = events
+        .iter()
+        .filter_map(|e| match e {
+            ScanEvent::NewEntry(stats) => Some(stats.path.clone()),
+            _ => None,
+        })
+        .collect();
+    assert!(paths.contains(&dir.join("src/main.rs")));
+    assert!(!paths
+        .iter()
+        .any(|p| p.starts_with(dir.join("node_modules"))));
+    assert!(!paths.contains(&dir.join("src/debug.log")));
+
+    let progress = events
+        .iter()
+        .rev()
+        .find_map(|e| match e {
+            ScanEvent::Progress(p) => Some(p.clone()),
+            _ => None,
+        })
+        .unwrap();
+    // The pruned directory counts once, its contents are never read.
+    assert_eq!(progress.excluded, 2);
+}
+
+#[test]
+fn test_exclude_matcher_patterns() {
+    use diskdoc::infrastructure::scanner::exclude::ExcludeMatcher;
+    use std::path::Path;
+
+    let matcher = ExcludeMatcher::new(
+        Path::new("/project"),
+        &[
+            "target".to_string(),
+            "build/cache".to_string(),
+            "*.tmp".to_string(),
+        ],
+    )
+    .unwrap();
+
+    assert!(matcher.is_excluded(Path::new("/project/target")));
+    assert!(matcher.is_excluded(Path::new("/project/sub/target")));
+    assert!(matcher.is_excluded(Path::new("/project/build/cache")));
+    assert!(matcher.is_excluded(Path::new("/project/a/b.tmp")));
+    assert!(!matcher.is_excluded(Path::new("/project/build/output")));
+
+    assert!(ExcludeMatcher::new(Path::new("/"), &["[".to_string()]).is_err());
+}
+
+#[test]
+fn test_cancelled_scan_completes_early() {
+    use diskdoc::domain::ports::{ScanEvent, Scanner};
+    use diskdoc::infrastructure::scanner::FsScanner;
+    use std::fs;
+
+    let dir = PathBuf::from("test_scan_cancel");
+    fs::create_dir_all(&dir).unwrap();
+    for i in 0..50 {
+        fs::write(dir.join(format!("file{}", i)), "x").unwrap();
+    }
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let handle = FsScanner::new().scan(&dir, tx);
+    handle.cancel();
+    let events: Vec<ScanEvent> = rx.iter().collect();
+    fs::remove_dir_all(&dir).unwrap();
+
+    let entries = events
+        .iter()
+        .filter(|e| matches!(e, ScanEvent::NewEntry(_)))
+        .count();
+    assert!(entries < 51);
+    assert!(matches!(events.last(), Some(ScanEvent::Complete)));
+}
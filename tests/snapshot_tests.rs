@@ -0,0 +1,102 @@
+use diskdoc::domain::entities::{ScanError, ScanOptions};
+use diskdoc::domain::ports::{ScanEvent, Scanner};
+use diskdoc::domain::tree::DirTree;
+use diskdoc::infrastructure::snapshot::{self, SnapshotStore};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+mod test_utils;
+use test_utils::{dir_entry, file_entry};
+
+fn sample_tree() -> DirTree {
+    let mut tree = DirTree::new("/data");
+    tree.insert(dir_entry("/data/projects", 0));
+    tree.insert(file_entry("/data/projects/big.iso", 5000));
+    tree.insert(file_entry("/data/notes.txt", 100));
+    tree
+}
+
+#[test]
+fn test_snapshot_save_list_and_load() {
+    let dir = PathBuf::from("test_snapshot_store");
+    let store = SnapshotStore::with_dir(&dir);
+    let options = ScanOptions {
+        one_file_system: true,
+        excludes: vec!["*.tmp".to_string()],
+        use_ignore_files: false,
+    };
+    let errors = vec![ScanError::other(
+        Some(PathBuf::from("/data/projects")),
+        "denied",
+    )];
+
+    let first = store.save(&sample_tree(), &errors, &options).unwrap();
+    let second = store.save(&sample_tree(), &[], &options).unwrap();
+    assert_ne!(first, second);
+
+    let listed = store.list().unwrap();
+    let latest = store.latest_for(Path::new("/data")).unwrap();
+    let missing = store.latest_for(Path::new("/elsewhere")).unwrap();
+    let (meta, scanner) = snapshot::open(&first).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(listed.len(), 2);
+    assert!(latest.is_some());
+    assert!(missing.is_none());
+
+    assert_eq!(meta.root, PathBuf::from("/data"));
+    assert_eq!(meta.entries, 3);
+    assert_eq!(meta.allocated_size, 5100);
+    assert_eq!(meta.errors, 1);
+    assert!(meta.options.one_file_system);
+    assert_eq!(meta.options.excludes, vec!["*.tmp".to_string()]);
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    scanner.scan(&meta.root, tx);
+    let mut tree = DirTree::new(&meta.root);
+    let mut replayed_errors = 0;
+    for event in rx {
+        match event {
+            ScanEvent::NewEntry(stats) => {
+                tree.insert(stats);
+            }
+            ScanEvent::Error(_) => replayed_errors += 1,
+            _ => {}
+        }
+    }
+    assert_eq!(tree.len(), 3);
+    assert_eq!(tree.node(tree.root()).allocated_size, 5100);
+    assert_eq!(replayed_errors, 1);
+}
+
+#[test]
+fn test_snapshot_load_tree() {
+    let dir = PathBuf::from("test_snapshot_load_tree");
+    let store = SnapshotStore::with_dir(&dir);
+    let errors = vec![ScanError::other(
+        Some(PathBuf::from("/data/projects")),
+        "denied",
+    )];
+    let file = store
+        .save(&sample_tree(), &errors, &ScanOptions::default())
+        .unwrap();
+    let loaded = snapshot::load_tree(&file);
+    fs::remove_dir_all(&dir).unwrap();
+
+    let (meta, tree, errors) = loaded.unwrap();
+    assert_eq!(meta.root, PathBuf::from("/data"));
+    assert_eq!(tree.len(), 3);
+    assert_eq!(tree.node(tree.root()).allocated_size, 5100);
+    assert_eq!(errors.len(), 1);
+    let projects = tree.find(Path::new("/data/projects")).unwrap();
+    assert!(tree.node(projects).incomplete);
+}
+
+#[test]
+fn test_snapshot_rejects_other_files() {
+    let path = PathBuf::from("test_snapshot_invalid.snap");
+    fs::write(&path, "not a snapshot").unwrap();
+    let result = snapshot::read_meta(&path);
+    fs::remove_file(&path).unwrap();
+    assert!(result.is_err());
+}
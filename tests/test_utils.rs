@@ -1,7 +1,44 @@
+// Each test crate uses only some of these helpers.
+#![allow(dead_code)]
+
 use anyhow::Result;
-use diskdoc::domain::entities::Recommendation;
+use diskdoc::domain::entities::{FileStats, FileType, Recommendation};
 use diskdoc::domain::ports::{Analyzer, Cleaner};
 use std::path::Path;
+use std::time::SystemTime;
+
+/// A regular file of `size` bytes, allocated and apparent alike.
+pub fn file_entry(path: impl AsRef<Path>, size: u64) -> FileStats {
+    FileStats {
+        path: path.as_ref().to_path_buf(),
+        size,
+        allocated_size: size,
+        is_dir: false,
+        modified: SystemTime::UNIX_EPOCH,
+        children_count: None,
+        file_type: FileType::Normal,
+        links: 1,
+        duplicate_inode: false,
+        is_mount_point: false,
+        inode: None,
+    }
+}
+
+/// A directory whose own entry takes `size` bytes.
+pub fn dir_entry(path: impl AsRef<Path>, size: u64) -> FileStats {
+    FileStats {
+        is_dir: true,
+        ..file_entry(path, size)
+    }
+}
+
+/// A regular file the scanner classified as `file_type`.
+pub fn typed_entry(path: impl AsRef<Path>, size: u64, file_type: FileType) -> FileStats {
+    FileStats {
+        file_type,
+        ..file_entry(path, size)
+    }
+}
 
 pub struct MockCleaner;
 impl MockCleaner {
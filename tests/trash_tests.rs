@@ -0,0 +1,115 @@
+use diskdoc::domain::entities::Removal;
+use diskdoc::domain::ports::Cleaner;
+use diskdoc::infrastructure::cleaner::FsCleaner;
+use diskdoc::infrastructure::trash::TrashCleaner;
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn test_trash_moves_items_with_trashinfo() {
+    let dir = std::env::current_dir().unwrap().join("test_trash_dir");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("data/sub")).unwrap();
+    fs::write(dir.join("data/old log.txt"), "content").unwrap();
+    fs::write(dir.join("data/sub/old log.txt"), "again").unwrap();
+
+    // Same filesystem as the data, so items go to the home trash.
+    let cleaner = TrashCleaner::with_home_trash(dir.join("Trash"));
+    assert_eq!(cleaner.removal(), Removal::Trash);
+    assert_eq!(FsCleaner::new().removal(), Removal::Permanent);
+
+    let size = cleaner.delete_item(&dir.join("data/old log.txt")).unwrap();
+    assert_eq!(size, 7);
+    cleaner
+        .delete_item(&dir.join("data/sub/old log.txt"))
+        .unwrap();
+    cleaner.delete_item(&dir.join("data/sub")).unwrap();
+
+    assert!(!dir.join("data/old log.txt").exists());
+    assert!(!dir.join("data/sub").exists());
+    let files = dir.join("Trash/files");
+    assert_eq!(
+        fs::read_to_string(files.join("old log.txt")).unwrap(),
+        "content"
+    );
+    // A second item with the same name gets a numbered name.
+    assert_eq!(
+        fs::read_to_string(files.join("old log.txt.2")).unwrap(),
+        "again"
+    );
+    assert!(files.join("sub").is_dir());
+
+    let info = fs::read_to_string(dir.join("Trash/info/old log.txt.trashinfo")).unwrap();
+    let lines: Vec<&str> = info.lines().collect();
+    assert_eq!(lines[0], "[Trash Info]");
+    let expected = format!(
+        "Path={}/data/old%20log.txt",
+        dir.display().to_string().replace(' ', "%20")
+    );
+    assert_eq!(lines[1], expected);
+    let date = lines[2].strip_prefix("DeletionDate=").unwrap();
+    assert_eq!(date.len(), "2024-03-09T14:05:00".len());
+    assert_eq!(&date[10..11], "T");
+    assert!(dir.join("Trash/info/old log.txt.2.trashinfo").exists());
+    assert!(dir.join("Trash/info/sub.trashinfo").exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_trash_reports_directory_contents_as_freed() {
+    let dir = std::env::current_dir().unwrap().join("test_trash_dir_size");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("data/build")).unwrap();
+    fs::write(dir.join("data/build/a.o"), vec![0; 1000]).unwrap();
+    fs::write(dir.join("data/build/b.o"), vec![0; 500]).unwrap();
+    let own = fs::metadata(dir.join("data/build")).unwrap().len();
+
+    let cleaner = TrashCleaner::with_home_trash(dir.join("Trash"));
+    let size = cleaner.delete_item(&dir.join("data/build")).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(size, own + 1500);
+}
+
+#[test]
+fn test_trash_missing_item_leaves_no_trashinfo() {
+    let dir = PathBuf::from("test_trash_missing_dir");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    let cleaner = TrashCleaner::with_home_trash(dir.join("Trash"));
+    assert!(cleaner.delete_item(&dir.join("missing")).is_err());
+    assert!(!dir.join("Trash/info/missing.trashinfo").exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_trash_skips_names_held_by_orphans() {
+    let dir = std::env::current_dir()
+        .unwrap()
+        .join("test_trash_orphan_dir");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("data")).unwrap();
+    fs::create_dir_all(dir.join("Trash/files")).unwrap();
+    fs::write(dir.join("Trash/files/report.txt"), "orphan").unwrap();
+    fs::write(dir.join("data/report.txt"), "new").unwrap();
+
+    let cleaner = TrashCleaner::with_home_trash(dir.join("Trash"));
+    cleaner.delete_item(&dir.join("data/report.txt")).unwrap();
+
+    let files = dir.join("Trash/files");
+    assert_eq!(
+        fs::read_to_string(files.join("report.txt")).unwrap(),
+        "orphan"
+    );
+    assert_eq!(
+        fs::read_to_string(files.join("report.txt.2")).unwrap(),
+        "new"
+    );
+    assert!(!dir.join("Trash/info/report.txt.trashinfo").exists());
+    assert!(dir.join("Trash/info/report.txt.2.trashinfo").exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
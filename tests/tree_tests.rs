@@ -0,0 +1,189 @@
+use diskdoc::domain::entities::{FileType, SizeMode};
+use diskdoc::domain::tree::DirTree;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+mod test_utils;
+use test_utils::{dir_entry, file_entry};
+
+#[test]
+fn test_insert_rolls_up_sizes() {
+    let mut tree = DirTree::new("/root");
+    tree.insert(dir_entry("/root/dir", 10));
+    tree.insert(file_entry("/root/dir/a", 100));
+    tree.insert(file_entry("/root/dir/sub/b", 50));
+
+    // The intermediate "sub" directory is created on demand.
+    let sub = tree.find(&PathBuf::from("/root/dir/sub")).unwrap();
+    assert!(tree.node(sub).is_dir);
+    assert_eq!(tree.node(sub).apparent_size, 50);
+
+    let dir = tree.find(&PathBuf::from("/root/dir")).unwrap();
+    assert_eq!(tree.node(dir).apparent_size, 160);
+    assert_eq!(tree.node(dir).descendants, 3);
+    assert_eq!(tree.stats(dir).children_count, Some(3));
+    assert_eq!(tree.node(tree.root()).apparent_size, 160);
+    assert_eq!(tree.len(), 4);
+
+    // Entries outside the root are ignored.
+    assert!(tree.insert(file_entry("/elsewhere/file", 1)).is_none());
+}
+
+#[test]
+fn test_reinsert_updates_size() {
+    let mut tree = DirTree::new("/root");
+    tree.insert(file_entry("/root/file", 100));
+    tree.insert(file_entry("/root/file", 40));
+
+    assert_eq!(tree.len(), 1);
+    assert_eq!(tree.node(tree.root()).apparent_size, 40);
+}
+
+#[test]
+fn test_path_reconstruction() {
+    let mut tree = DirTree::new("/root");
+    let id = tree.insert(file_entry("/root/a/b/c.txt", 1)).unwrap();
+
+    assert_eq!(tree.path(id), PathBuf::from("/root/a/b/c.txt"));
+    assert_eq!(tree.name(id), "c.txt");
+    assert_eq!(tree.path(tree.root()), PathBuf::from("/root"));
+}
+
+#[test]
+fn test_remove_subtree() {
+    let mut tree = DirTree::new("/root");
+    tree.insert(file_entry("/root/keep", 5));
+    tree.insert(file_entry("/root/dir/a", 100));
+    tree.insert(file_entry("/root/dir/b", 200));
+
+    let dir = tree.find(&PathBuf::from("/root/dir")).unwrap();
+    assert_eq!(tree.remove(dir).unwrap().size, 300);
+
+    assert!(tree.find(&PathBuf::from("/root/dir")).is_none());
+    assert!(tree.find(&PathBuf::from("/root/dir/a")).is_none());
+    assert_eq!(tree.children(tree.root()).len(), 1);
+    assert_eq!(tree.node(tree.root()).apparent_size, 5);
+    assert_eq!(tree.len(), 1);
+    assert_eq!(tree.iter().count(), 1);
+
+    // Freed slots are reused without resurrecting old children.
+    tree.insert(dir_entry("/root/dir", 0));
+    let dir = tree.find(&PathBuf::from("/root/dir")).unwrap();
+    assert!(tree.children(dir).is_empty());
+}
+
+#[test]
+fn test_allocated_size_rolls_up() {
+    let mut tree = DirTree::new("/root");
+    let mut sparse = file_entry("/root/dir/disk.img", 1_000_000);
+    sparse.allocated_size = 4096;
+    tree.insert(sparse);
+
+    let dir = tree.find(&PathBuf::from("/root/dir")).unwrap();
+    assert_eq!(tree.node(dir).size(SizeMode::Apparent), 1_000_000);
+    assert_eq!(tree.node(dir).size(SizeMode::Disk), 4096);
+}
+
+#[test]
+fn test_duplicate_inodes_are_counted_once() {
+    let mut tree = DirTree::new("/root");
+    let mut first = file_entry("/root/a/file", 100);
+    first.links = 2;
+    let mut second = file_entry("/root/b/file", 100);
+    second.links = 2;
+    second.duplicate_inode = true;
+    tree.insert(first);
+    let duplicate = tree.insert(second).unwrap();
+
+    // The duplicate keeps its own size but does not add to its ancestors.
+    assert_eq!(tree.node(duplicate).size(SizeMode::Apparent), 100);
+    let b = tree.find(&PathBuf::from("/root/b")).unwrap();
+    assert_eq!(tree.node(b).apparent_size, 0);
+    assert_eq!(tree.node(tree.root()).apparent_size, 100);
+
+    // Removing it leaves the counted total untouched.
+    tree.remove(duplicate);
+    assert_eq!(tree.node(tree.root()).apparent_size, 100);
+}
+
+#[test]
+fn test_mark_incomplete_flags_nearest_known_ancestor() {
+    let mut tree = DirTree::new("/root");
+    tree.insert(file_entry("/root/a/file", 100));
+    tree.insert(file_entry("/root/b/file", 100));
+
+    tree.mark_incomplete(&PathBuf::from("/root/a/missing/deeper"));
+
+    let a = tree.find(&PathBuf::from("/root/a")).unwrap();
+    let b = tree.find(&PathBuf::from("/root/b")).unwrap();
+    assert!(tree.node(a).incomplete);
+    assert!(tree.node(tree.root()).incomplete);
+    assert!(!tree.node(b).incomplete);
+}
+
+#[test]
+fn test_count_mode_counts_entries() {
+    let mut tree = DirTree::new("/root");
+    tree.insert(file_entry("/root/dir/a", 100));
+    tree.insert(file_entry("/root/dir/b", 100));
+
+    let dir = tree.find(&PathBuf::from("/root/dir")).unwrap();
+    assert_eq!(tree.node(dir).size(SizeMode::Count), 3);
+    assert_eq!(tree.node(dir).own_size(SizeMode::Count), 1);
+    assert_eq!(tree.node(tree.root()).size(SizeMode::Count), 4);
+    assert_eq!(tree.stats(dir).size_for(SizeMode::Count), 3);
+}
+
+#[test]
+fn test_sort_by_spec() {
+    use diskdoc::domain::entities::{SortKey, SortOrder, SortSpec};
+    use std::time::Duration;
+
+    let mut tree = DirTree::new("/root");
+    let mut log = file_entry("/root/b.LOG", 300);
+    log.file_type = FileType::Log;
+    log.modified = SystemTime::UNIX_EPOCH + Duration::from_secs(30);
+    tree.insert(log);
+    let mut text = file_entry("/root/a.txt", 100);
+    text.modified = SystemTime::UNIX_EPOCH + Duration::from_secs(10);
+    tree.insert(text);
+    tree.insert(file_entry("/root/C/x", 60));
+    tree.insert(file_entry("/root/C/y", 60));
+
+    let names = |spec: SortSpec| {
+        let mut ids = tree.children(tree.root()).to_vec();
+        tree.sort(&mut ids, spec, SizeMode::Disk);
+        ids.iter()
+            .map(|&id| tree.name(id).to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(names(SortSpec::default()), vec!["b.LOG", "C", "a.txt"]);
+    assert_eq!(
+        names(SortSpec::new(SortKey::Name)),
+        vec!["a.txt", "b.LOG", "C"]
+    );
+    assert_eq!(
+        names(SortSpec::new(SortKey::Modified)),
+        vec!["b.LOG", "a.txt", "C"]
+    );
+    assert_eq!(
+        names(SortSpec::new(SortKey::Count)),
+        vec!["C", "a.txt", "b.LOG"]
+    );
+    assert_eq!(
+        names(SortSpec::new(SortKey::Extension)),
+        vec!["C", "b.LOG", "a.txt"]
+    );
+    assert_eq!(
+        names(SortSpec::new(SortKey::Type)),
+        vec!["b.LOG", "a.txt", "C"]
+    );
+
+    let dirs_first = SortSpec {
+        key: SortKey::Name,
+        order: SortOrder::Desc,
+        dirs_first: true,
+    };
+    assert_eq!(names(dirs_first), vec!["C", "b.LOG", "a.txt"]);
+}
@@ -0,0 +1,86 @@
+use diskdoc::application::app::App;
+use diskdoc::domain::treemap::{neighbour, squarify, Direction, Rect};
+use std::path::PathBuf;
+
+mod test_utils;
+use test_utils::{dir_entry, file_entry, MockAnalyzer, MockCleaner};
+
+#[test]
+fn test_squarify_fills_area_proportionally() {
+    let area = Rect::new(0.0, 0.0, 6.0, 4.0);
+    let sizes = [6, 6, 4, 3, 2, 2, 1];
+    let tiles = squarify(&sizes, area);
+
+    assert_eq!(tiles.len(), sizes.len());
+    let total: f64 = tiles.iter().map(|t| t.area()).sum();
+    assert!((total - area.area()).abs() < 1e-9);
+    for (tile, &size) in tiles.iter().zip(&sizes) {
+        assert!((tile.area() - size as f64).abs() < 1e-9);
+        assert!(tile.x >= -1e-9 && tile.right() <= 6.0 + 1e-9);
+        assert!(tile.y >= -1e-9 && tile.bottom() <= 4.0 + 1e-9);
+    }
+    // The classic example: the two largest tiles form a square-ish column.
+    assert!((tiles[0].width - 3.0).abs() < 1e-9);
+    assert!((tiles[0].height - 2.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_squarify_zero_sizes() {
+    let area = Rect::new(0.0, 0.0, 10.0, 10.0);
+    assert!(squarify(&[0, 0], area).iter().all(|t| t.area() == 0.0));
+
+    let tiles = squarify(&[5, 0], area);
+    assert!((tiles[0].area() - 100.0).abs() < 1e-9);
+    assert_eq!(tiles[1].area(), 0.0);
+}
+
+#[test]
+fn test_neighbour_navigation() {
+    // [0][1]
+    // [2][3]
+    let tiles = [
+        Rect::new(0.0, 0.0, 5.0, 5.0),
+        Rect::new(5.0, 0.0, 5.0, 5.0),
+        Rect::new(0.0, 5.0, 5.0, 5.0),
+        Rect::new(5.0, 5.0, 5.0, 5.0),
+    ];
+    assert_eq!(neighbour(&tiles, 0, Direction::Right), Some(1));
+    assert_eq!(neighbour(&tiles, 0, Direction::Down), Some(2));
+    assert_eq!(neighbour(&tiles, 3, Direction::Left), Some(2));
+    assert_eq!(neighbour(&tiles, 3, Direction::Up), Some(1));
+    assert_eq!(neighbour(&tiles, 0, Direction::Left), None);
+    assert_eq!(neighbour(&tiles, 0, Direction::Up), None);
+}
+
+#[test]
+fn test_treemap_zoom_in_and_out() {
+    let mut app = App::new(
+        "/root".to_string(),
+        Box::new(MockCleaner::new()),
+        Box::new(MockAnalyzer::new()),
+    );
+    app.is_scanning = false;
+    app.add_entry(file_entry("/root/small.txt", 100));
+    app.add_entry(dir_entry("/root/big", 0));
+    app.add_entry(file_entry("/root/big/a.bin", 800));
+    app.add_entry(file_entry("/root/empty", 0));
+
+    // Empty entries get no tile; the largest comes first.
+    let layout = app.treemap_layout(Rect::new(0.0, 0.0, 40.0, 20.0));
+    assert_eq!(layout.len(), 2);
+    assert_eq!(app.tree.path(layout[0].0), PathBuf::from("/root/big"));
+
+    app.treemap_move(Direction::Right, Rect::new(0.0, 0.0, 40.0, 20.0));
+    assert_eq!(app.treemap_selection, 1);
+    app.treemap_enter(); // a file: nothing happens
+    assert_eq!(app.current_path, PathBuf::from("/root"));
+
+    app.treemap_selection = 0;
+    app.treemap_enter();
+    assert_eq!(app.current_path, PathBuf::from("/root/big"));
+    assert_eq!(app.treemap_selection, 0);
+
+    app.treemap_up();
+    assert_eq!(app.current_path, PathBuf::from("/root"));
+    assert_eq!(app.treemap_selection, 0);
+}
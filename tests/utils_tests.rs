@@ -7,3 +7,49 @@ fn test_format_size() {
     assert_eq!(format_size(1024 * 1024), "1 MiB");
     assert_eq!(format_size(1024 * 1024 * 1024), "1 GiB");
 }
+
+#[test]
+fn test_format_duration() {
+    use diskdoc::utils::format_duration;
+    use std::time::Duration;
+
+    assert_eq!(format_duration(Duration::from_secs(5)), "5s");
+    assert_eq!(format_duration(Duration::from_secs(65)), "1m 05s");
+    assert_eq!(format_duration(Duration::from_secs(3725)), "1h 02m");
+}
+
+#[test]
+fn test_sparkline_and_format_date() {
+    use diskdoc::utils::{format_date, sparkline};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    assert_eq!(sparkline(&[0, 7, 14]), "▁▅█");
+    assert_eq!(sparkline(&[]), "");
+    assert_eq!(format_date(UNIX_EPOCH), "1970-01-01 00:00");
+    assert_eq!(
+        format_date(UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+        "2023-11-14 22:13"
+    );
+}
+
+#[test]
+fn test_usage_bar() {
+    use diskdoc::utils::usage_bar;
+
+    assert_eq!(usage_bar(0.0, 4), "░░░░");
+    assert_eq!(usage_bar(0.5, 4), "██░░");
+    assert_eq!(usage_bar(1.5, 4), "████");
+}
+
+#[test]
+fn test_format_amount() {
+    use diskdoc::domain::entities::SizeMode;
+    use diskdoc::utils::{format_amount, format_amount_delta, format_count};
+
+    assert_eq!(format_count(0), "0");
+    assert_eq!(format_count(999), "999");
+    assert_eq!(format_count(1_234_567), "1,234,567");
+    assert_eq!(format_amount(1024, SizeMode::Disk), "1 KiB");
+    assert_eq!(format_amount(1024, SizeMode::Count), "1,024");
+    assert_eq!(format_amount_delta(-1500, SizeMode::Count), "-1,500");
+}